@@ -0,0 +1,227 @@
+//! `#[differs(...)]` attribute parsing for **`#[derive(Diff)]`**.
+
+use syn::{
+    Attribute, Expr, ExprPath, LitStr, Member, Token, WherePredicate, punctuated::Punctuated,
+};
+
+/// Options on the deriving type itself.
+#[derive(Default)]
+pub struct ContainerAttrs {
+    /// `bound = "T: Trait, …"` – replaces the inferred where-clause predicates.
+    pub bound: Option<Vec<WherePredicate>>,
+    /// `old_new` – every field but the containers reports `(old, new)`.
+    pub old_new: bool,
+    /// `no_self` – no `self_` variant leading the field changes.
+    pub no_self: bool,
+    /// `owned` – also generate `'static` `…Owned` change types.
+    pub owned: bool,
+    /// `serde` – derive `Serialize` on the change and snapshot types, and
+    /// `Deserialize` as well on their `…Owned` mirrors; implement
+    /// `JsonPatchOps` on the change types. Needs the `serde` feature of
+    /// differs (on by default).
+    pub serde: bool,
+    /// `patch` – implement `ApplyChanges` with the `…Owned` change type
+    /// (implies `owned`).
+    pub patch: bool,
+}
+
+/// Options on a single field.
+#[derive(Default, Clone)]
+pub struct FieldAttrs {
+    /// `skip` – neither diffed nor reported.
+    pub skip: bool,
+    /// `old_new` – the field is compared whole and its change carries
+    /// `(old, new)` instead of `new`.
+    pub old_new: bool,
+    /// `key = id` / `key_fn = path` – match `Vec` elements by identity.
+    pub key: Option<VecKey>,
+    /// `algorithm = "myers" | "patience" | "hash"` – how `Vec` fields are diffed.
+    /// Only applies where the field is spelled `Vec<T>` / `VecDeque<T>`; a
+    /// type alias of one is diffed by its `HasChanges` impl (Myers).
+    pub algorithm: Option<VecAlgorithm>,
+    /// `ranges` – contiguous `Vec` insertions / removals become one
+    /// `AddedRange` / `RemovedRange`.
+    pub ranges: bool,
+    /// `ptr_eq` – skip `Rc` / `Arc` fields that still share their allocation.
+    pub ptr_eq: bool,
+    /// `scalar` / `opaque` – compare with `PartialEq` and report the new value,
+    /// whatever the type.
+    pub scalar: bool,
+    /// `with = path` – a user-supplied diff function,
+    /// `fn(&T, &T) -> bool`, returning whether the field changed; the new
+    /// value is reported as `scalar` does.
+    pub with: Option<ExprPath>,
+    /// `with_module = path` – a module standing in for the field's
+    /// `HasChanges` impl, as serde's `with` does for `Serialize`. It must
+    /// expose both
+    ///
+    /// ```ignore
+    /// mod stamp_delta {
+    ///     pub type Change<'a> = i64;
+    ///     pub fn collect_changes<'a>(old: &'a Stamp, new: &'a Stamp, out: &mut Vec<Change<'a>>) {
+    ///         if old != new {
+    ///             out.push(new.0 as i64 - old.0 as i64);
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub with_module: Option<ExprPath>,
+    /// `eq = path` / `epsilon = ..` / `ulps = ..` – replaces `==` for the field.
+    pub eq: Option<FieldEq>,
+}
+
+/// Custom equality for a field.
+#[derive(Clone)]
+pub enum FieldEq {
+    /// `eq = path` – `fn(&T, &T) -> bool`.
+    Fn(ExprPath),
+    /// `with = path` – `fn(&T, &T) -> bool`, true when the values differ.
+    Changed(ExprPath),
+    /// `epsilon = 1e-9` – absolute float tolerance.
+    Epsilon(Expr),
+    /// `ulps = 4` – float tolerance in units in the last place.
+    Ulps(Expr),
+}
+
+/// Ordered diff algorithm for a `Vec` field.
+#[derive(Clone, Copy, PartialEq)]
+pub enum VecAlgorithm {
+    /// Value → index-queue matching; every shift is a `Moved` (default).
+    Hash,
+    /// Myers LCS; only needs `PartialEq`.
+    Myers,
+    /// Patience LCS; only needs `PartialEq`.
+    Patience,
+}
+
+/// How elements of a keyed `Vec` field are identified.
+#[derive(Clone)]
+pub enum VecKey {
+    /// `key = id` (or `key = "id"`) – a field of the element.
+    Field(Member),
+    /// `key_fn = path` – `fn(&T) -> K`.
+    Fn(ExprPath),
+}
+
+impl ContainerAttrs {
+    pub fn parse(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut out = Self::default();
+        for attr in attrs.iter().filter(|a| a.path().is_ident("differs")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("bound") {
+                    let lit: LitStr = meta.value()?.parse()?;
+                    let preds =
+                        lit.parse_with(Punctuated::<WherePredicate, Token![,]>::parse_terminated)?;
+                    out.bound = Some(preds.into_iter().collect());
+                    Ok(())
+                } else if meta.path.is_ident("old_new") {
+                    out.old_new = true;
+                    Ok(())
+                } else if meta.path.is_ident("no_self") {
+                    out.no_self = true;
+                    Ok(())
+                } else if meta.path.is_ident("owned") {
+                    out.owned = true;
+                    Ok(())
+                } else if meta.path.is_ident("serde") {
+                    out.serde = true;
+                    Ok(())
+                } else if meta.path.is_ident("patch") {
+                    out.patch = true;
+                    out.owned = true;
+                    Ok(())
+                } else {
+                    Err(meta.error("unknown `differs` container attribute"))
+                }
+            })?;
+        }
+        Ok(out)
+    }
+}
+
+impl FieldAttrs {
+    pub fn parse(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut out = Self::default();
+        for attr in attrs.iter().filter(|a| a.path().is_ident("differs")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("skip") {
+                    out.skip = true;
+                    Ok(())
+                } else if meta.path.is_ident("old_new") {
+                    out.old_new = true;
+                    Ok(())
+                } else if meta.path.is_ident("ptr_eq") {
+                    out.ptr_eq = true;
+                    Ok(())
+                } else if meta.path.is_ident("scalar") || meta.path.is_ident("opaque") {
+                    out.scalar = true;
+                    Ok(())
+                } else if meta.path.is_ident("with") || meta.path.is_ident("with_module") {
+                    let value = meta.value()?;
+                    let path = if value.peek(LitStr) {
+                        value.parse::<LitStr>()?.parse()?
+                    } else {
+                        value.parse()?
+                    };
+                    if meta.path.is_ident("with") {
+                        out.with = Some(path);
+                    } else {
+                        /* `#with_module::Change<'a>` / `#with_module::collect_changes` */
+                        out.with_module = Some(path);
+                    }
+                    Ok(())
+                } else if meta.path.is_ident("eq")
+                    || meta.path.is_ident("epsilon")
+                    || meta.path.is_ident("ulps")
+                {
+                    if out.eq.is_some() {
+                        return Err(meta.error("only one of `eq`, `epsilon` and `ulps` is allowed"));
+                    }
+                    let value = meta.value()?;
+                    out.eq = Some(if meta.path.is_ident("epsilon") {
+                        FieldEq::Epsilon(value.parse()?)
+                    } else if meta.path.is_ident("ulps") {
+                        FieldEq::Ulps(value.parse()?)
+                    } else if value.peek(LitStr) {
+                        FieldEq::Fn(value.parse::<LitStr>()?.parse()?)
+                    } else {
+                        FieldEq::Fn(value.parse()?)
+                    });
+                    Ok(())
+                } else if meta.path.is_ident("ranges") {
+                    out.ranges = true;
+                    Ok(())
+                } else if meta.path.is_ident("key") {
+                    let value = meta.value()?;
+                    let member = if value.peek(LitStr) {
+                        value.parse::<LitStr>()?.parse()?
+                    } else {
+                        value.parse()?
+                    };
+                    out.key = Some(VecKey::Field(member));
+                    Ok(())
+                } else if meta.path.is_ident("algorithm") {
+                    let lit: LitStr = meta.value()?.parse()?;
+                    out.algorithm = Some(match lit.value().as_str() {
+                        "hash" => VecAlgorithm::Hash,
+                        "myers" => VecAlgorithm::Myers,
+                        "patience" => VecAlgorithm::Patience,
+                        _ => {
+                            return Err(syn::Error::new(
+                                lit.span(),
+                                "expected \"myers\", \"patience\" or \"hash\"",
+                            ));
+                        }
+                    });
+                    Ok(())
+                } else if meta.path.is_ident("key_fn") {
+                    out.key = Some(VecKey::Fn(meta.value()?.parse()?));
+                    Ok(())
+                } else {
+                    Err(meta.error("unknown `differs` field attribute"))
+                }
+            })?;
+        }
+        Ok(out)
+    }
+}
@@ -1,45 +1,27 @@
 //! **`#[derive(Diff)]`** implementation.
 
+use std::collections::HashSet;
+
 use proc_macro::TokenStream;
-use quote::{format_ident, quote, quote_spanned};
+use proc_macro2::{Span, TokenStream as TokenStream2, TokenTree};
+use quote::{ToTokens, format_ident, quote, quote_spanned};
 use syn::{
-    AngleBracketedGenericArguments as ABGA, Attribute, Data, DeriveInput, Fields, GenericArgument,
-    Ident, PathArguments, PathSegment, Type, parse_macro_input,
+    AngleBracketedGenericArguments as ABGA, Data, DataEnum, DataStruct, DeriveInput, Fields,
+    GenericArgument, GenericParam, Generics, Ident, Lifetime, LifetimeParam, PathArguments, Type,
+    WherePredicate, ext::IdentExt, parse_macro_input, parse_quote, spanned::Spanned,
 };
 
+use crate::attrs::{ContainerAttrs, FieldAttrs, FieldEq, VecAlgorithm, VecKey};
+
 /* ------------------------------------------------------------------------- */
 /* Helper predicates                                                         */
 /* ------------------------------------------------------------------------- */
 
-fn is_std_string(ty: &Type) -> bool {
-    matches!(
-        ty,
-        Type::Path(tp)
-            if tp.qself.is_none()
-            && tp.path.segments.last().is_some_and(
-                |seg| seg.ident == "String" && tp.path.segments.len() == 1
-            )
-    )
-}
-
-fn is_primitive(ty: &Type) -> bool {
-    const PRIMS: &[&str] = &[
-        "u8", "u16", "u32", "u64", "u128", "usize", "i8", "i16", "i32", "i64", "i128", "isize",
-        "f32", "f64", "bool", "char",
-    ];
-    matches!(
-        ty,
-        Type::Path(tp)
-            if tp.qself.is_none()
-            && tp.path.segments.len()==1
-            && PRIMS.contains(&tp.path.segments.last().unwrap().ident.to_string().as_str())
-    )
-}
-
-fn has_skip_attr(attrs: &[Attribute]) -> bool {
-    attrs.iter().any(|a| {
-        a.path().is_ident("differs") && a.parse_args::<Ident>().is_ok_and(|id| id == "skip")
-    })
+/// Types diffed whole through their own `HasChanges` / `ApplyChanges` impls:
+/// every path type but the containers and pointers the derive looks into.
+/// `pair` (`old_new`) fields are compared whole instead.
+fn through_trait(ty: &Type, pair: bool) -> bool {
+    !pair && matches!(ty, Type::Path(_)) && container_kind(ty).is_none() && pointee(ty).is_none()
 }
 
 /* ------------------------------------------------------------------------- */
@@ -47,43 +29,986 @@ fn has_skip_attr(attrs: &[Attribute]) -> bool {
 /* ------------------------------------------------------------------------- */
 
 enum Container<'a> {
-    Vec(&'a Type),
-    Set(&'a Type),
-    Map(&'a Type, &'a Type),
+    /// `Vec<T>`, or `VecDeque<T>` (`deque`).
+    Vec {
+        elem: &'a Type,
+        deque: bool,
+    },
+    Set(&'a Type, Order),
+    Map(&'a Type, &'a Type, Order),
+    Option(&'a Type),
+}
+
+/// How a set / map keys its entries.
+#[derive(Clone, Copy, PartialEq)]
+enum Order {
+    /// `HashSet` / `HashMap` / `IndexSet` / `IndexMap` (`Hash + Eq`).
+    Hashed,
+    /// `BTreeSet` / `BTreeMap` (`Ord`); changes come out in key order.
+    Sorted,
 }
 
+/// Containers are recognised by the last path segment as written, before
+/// any alias is resolved: `Vec<u32>` gets the derive's own matching below,
+/// while `type Ids = Vec<u32>` (or `std::vec::Vec` behind a newtype) is an
+/// opaque nested type and goes through the library's `HasChanges` impl.
 fn container_kind(ty: &Type) -> Option<Container<'_>> {
     let Type::Path(tp) = ty else { return None };
     let seg = tp.path.segments.last()?;
+    let PathArguments::AngleBracketed(ABGA { args, .. }) = &seg.arguments else {
+        return None;
+    };
+    let mut types = args.iter().filter_map(|arg| match arg {
+        GenericArgument::Type(ty) => Some(ty),
+        _ => None,
+    });
+    let (first, second) = (types.next(), types.next());
+    let indexmap = cfg!(feature = "indexmap");
+
+    Some(match seg.ident.to_string().as_str() {
+        "Vec" => Container::Vec {
+            elem: first?,
+            deque: false,
+        },
+        "VecDeque" => Container::Vec {
+            elem: first?,
+            deque: true,
+        },
+        "HashSet" => Container::Set(first?, Order::Hashed),
+        "IndexSet" if indexmap => Container::Set(first?, Order::Hashed),
+        "BTreeSet" => Container::Set(first?, Order::Sorted),
+        "HashMap" => Container::Map(first?, second?, Order::Hashed),
+        "IndexMap" if indexmap => Container::Map(first?, second?, Order::Hashed),
+        "BTreeMap" => Container::Map(first?, second?, Order::Sorted),
+        "Option" => Container::Option(first?),
+        _ => return None,
+    })
+}
+
+/// Smart pointers the derive sees through to their pointee.
+#[derive(Clone, Copy, PartialEq)]
+enum Pointer {
+    Box,
+    Rc,
+    Arc,
+    Cow,
+}
+
+fn pointee(ty: &Type) -> Option<(Pointer, &Type)> {
+    let Type::Path(tp) = ty else { return None };
+    let seg = tp.path.segments.last()?;
+    let kind = match seg.ident.to_string().as_str() {
+        "Box" => Pointer::Box,
+        "Rc" => Pointer::Rc,
+        "Arc" => Pointer::Arc,
+        "Cow" => Pointer::Cow,
+        _ => return None,
+    };
+    let PathArguments::AngleBracketed(ABGA { args, .. }) = &seg.arguments else {
+        return None;
+    };
+    args.iter().find_map(|arg| match arg {
+        GenericArgument::Type(inner) => Some((kind, inner)),
+        _ => None,
+    })
+}
+
+/* ------------------------------------------------------------------------- */
+/* Generics                                                                  */
+/* ------------------------------------------------------------------------- */
+
+/// Per-derive state shared by the field generators.
+struct Ctx {
+    /// Lifetime borrowed by snapshots and changes (`'a` unless the input
+    /// already declares it).
+    lt: Lifetime,
+    /// Type parameters of the input.
+    type_params: Vec<Ident>,
+    /// Where-predicates inferred from the field types that mention a type
+    /// parameter.
+    bounds: Vec<WherePredicate>,
+    /// Container-level `#[differs(old_new)]`.
+    old_new: bool,
+}
 
-    match seg.ident.to_string().as_str() {
-        "Vec" => {
-            if let PathArguments::AngleBracketed(ABGA { args, .. }) = &seg.arguments {
-                if let Some(GenericArgument::Type(inner)) = args.first() {
-                    return Some(Container::Vec(inner));
+impl Ctx {
+    fn new(generics: &Generics, attrs: &ContainerAttrs) -> Self {
+        let taken = |name: &str| generics.lifetimes().any(|l| l.lifetime.ident == name);
+        let lt = if taken("a") {
+            Lifetime::new("'__differs", Span::call_site())
+        } else {
+            Lifetime::new("'a", Span::call_site())
+        };
+        let type_params: Vec<Ident> = generics.type_params().map(|p| p.ident.clone()).collect();
+        Self {
+            lt,
+            type_params,
+            bounds: Vec::new(),
+            old_new: attrs.old_new,
+        }
+    }
+
+    /// Does `ty` mention one of the input's type parameters?
+    fn is_generic(&self, ty: &Type) -> bool {
+        fn walk(tokens: TokenStream2, params: &[Ident]) -> bool {
+            tokens.into_iter().any(|tt| match tt {
+                TokenTree::Ident(id) => params.contains(&id),
+                TokenTree::Group(g) => walk(g.stream(), params),
+                _ => false,
+            })
+        }
+        !self.type_params.is_empty() && walk(ty.to_token_stream(), &self.type_params)
+    }
+
+    /// Record `ty: #bound` if `ty` depends on a type parameter.
+    fn bound(&mut self, ty: &Type, bound: TokenStream2) {
+        if self.is_generic(ty) {
+            self.bounds.push(parse_quote!(#ty: #bound));
+        }
+    }
+
+    /// Generics of the generated snapshot / change types: the input's own
+    /// parameters prefixed by the borrow lifetime, which they must outlive.
+    fn change_generics(&self, generics: &Generics, attrs: &ContainerAttrs) -> Generics {
+        let lt = &self.lt;
+        let mut out = generics.clone();
+        out.params
+            .insert(0, GenericParam::Lifetime(LifetimeParam::new(lt.clone())));
+        let where_clause = out.make_where_clause();
+        for param in &generics.params {
+            match param {
+                GenericParam::Type(p) => {
+                    let id = &p.ident;
+                    where_clause.predicates.push(parse_quote!(#id: #lt));
+                }
+                GenericParam::Lifetime(p) => {
+                    let l = &p.lifetime;
+                    where_clause.predicates.push(parse_quote!(#l: #lt));
                 }
+                GenericParam::Const(_) => {}
             }
         }
-        "HashSet" => {
-            if let PathArguments::AngleBracketed(ABGA { args, .. }) = &seg.arguments {
-                if let Some(GenericArgument::Type(inner)) = args.first() {
-                    return Some(Container::Set(inner));
-                }
+        where_clause.predicates.extend(self.predicates(attrs));
+        out
+    }
+
+    /// Input generics with the inferred (or `bound = "..."`) predicates.
+    fn impl_generics(&self, generics: &Generics, attrs: &ContainerAttrs) -> Generics {
+        let mut out = generics.clone();
+        out.make_where_clause()
+            .predicates
+            .extend(self.predicates(attrs));
+        out
+    }
+
+    fn predicates(&self, attrs: &ContainerAttrs) -> Vec<WherePredicate> {
+        attrs.bound.clone().unwrap_or_else(|| self.bounds.clone())
+    }
+}
+
+/* ------------------------------------------------------------------------- */
+/* Per-field code generation                                                 */
+/* ------------------------------------------------------------------------- */
+
+/// Borrowed snapshot type + initialiser for one field; `src` evaluates to `&'a #ty`.
+fn snapshot_field(
+    ctx: &Ctx,
+    ty: &Type,
+    span: Span,
+    src: TokenStream2,
+) -> (TokenStream2, TokenStream2) {
+    let lt = &ctx.lt;
+    if let Some((_, inner)) = pointee(ty) {
+        snapshot_field(ctx, inner, span, quote_spanned!(span=> &**#src))
+    } else {
+        (quote_spanned!(span=> &#lt #ty), src)
+    }
+}
+
+/// Generated `Change` variant payload + diff logic for one field.
+struct FieldDiff {
+    ty: TokenStream2,
+    /// `old_new`: the variant carries `ty` twice.
+    pair: bool,
+    arm: TokenStream2,
+    /// `#[differs(patch)]`: a `Result<(), PatchError>` expression applying
+    /// `changes` (a `Vec` of owned payload refs, or of `(old, new)` ref
+    /// pairs) to `target: &mut #ty`; `None` when the field cannot be patched.
+    apply: Option<TokenStream2>,
+}
+
+impl FieldDiff {
+    /// Payload types of the field's `Change` variant.
+    fn payload(&self) -> Vec<&TokenStream2> {
+        if self.pair {
+            vec![&self.ty, &self.ty]
+        } else {
+            vec![&self.ty]
+        }
+    }
+}
+
+/// `old` / `new` evaluate to `&'a #ty`; `wrap` lifts a field-level change
+/// into the value pushed onto `out`.
+fn field_diff(
+    ctx: &mut Ctx,
+    ty: &Type,
+    attrs: &FieldAttrs,
+    span: Span,
+    old: TokenStream2,
+    new: TokenStream2,
+    wrap: &dyn Fn(TokenStream2) -> TokenStream2,
+) -> syn::Result<FieldDiff> {
+    let lt = ctx.lt.clone();
+    let not_scalar = || {
+        if attrs.old_new {
+            Err(syn::Error::new(
+                span,
+                "`old_new` does not apply to containers, arrays, tuples or `with_module` fields",
+            ))
+        } else {
+            Ok(())
+        }
+    };
+
+    /* `scalar` / `eq` / `with` / `with_module`: the field's own diff is
+     * replaced wholesale */
+    let given = [
+        attrs.eq.is_some(),
+        attrs.with.is_some(),
+        attrs.with_module.is_some(),
+    ];
+    if attrs.scalar || given.contains(&true) {
+        if attrs.key.is_some() || attrs.algorithm.is_some() || attrs.ranges || attrs.ptr_eq {
+            return Err(syn::Error::new(
+                span,
+                "`scalar` / `eq` / `with` / `with_module` cannot be combined with `key`, `key_fn`, `algorithm`, `ranges` or `ptr_eq`",
+            ));
+        }
+        if given.iter().filter(|g| **g).count() > 1 {
+            return Err(syn::Error::new(
+                span,
+                "only one of `eq` / `epsilon` / `ulps`, `with` and `with_module` can be given",
+            ));
+        }
+        let Some(with_module) = &attrs.with_module else {
+            let changed = attrs.with.clone().map(FieldEq::Changed);
+            return Ok(scalar_diff(
+                ctx,
+                ty,
+                changed.as_ref().or(attrs.eq.as_ref()),
+                attrs.old_new || ctx.old_new,
+                span,
+                old,
+                new,
+                wrap,
+            ));
+        };
+        if attrs.scalar {
+            return Err(syn::Error::new(
+                span,
+                "`with_module` cannot be combined with `scalar`",
+            ));
+        }
+        not_scalar()?;
+        let nested = wrap(quote!(c));
+        /* a function path given for the module fails here, at the attribute */
+        let span = with_module.span();
+        return Ok(FieldDiff {
+            pair: false,
+            apply: None,
+            ty: quote_spanned!(span=> #with_module::Change<#lt>),
+            arm: quote_spanned!(span=>{
+                let mut _subs = Vec::new();
+                #with_module::collect_changes(#old,#new,&mut _subs);
+                out.extend(_subs.into_iter().map(|c| #nested));
+            }),
+        });
+    }
+
+    /* Box<T> / Rc<T> / Arc<T> / Cow<T>: diff the pointee; one the derive
+     * does not look into goes through the pointer's own impls */
+    let pair = attrs.old_new || ctx.old_new;
+    if let Some((pointer, inner)) = pointee(ty) {
+        let diff = if through_trait(inner, pair) {
+            trait_diff(ctx, ty, span, old.clone(), new.clone(), wrap)
+        } else {
+            let diff = field_diff(
+                ctx,
+                inner,
+                &FieldAttrs {
+                    ptr_eq: false,
+                    ..attrs.clone()
+                },
+                span,
+                quote!(&**#old),
+                quote!(&**#new),
+                wrap,
+            )?;
+            /* patch the pointee in place */
+            let target = match pointer {
+                Pointer::Box => quote!(&mut **target),
+                Pointer::Rc => quote!(::std::rc::Rc::make_mut(target)),
+                Pointer::Arc => quote!(::std::sync::Arc::make_mut(target)),
+                Pointer::Cow => quote!(::std::borrow::Cow::to_mut(target)),
+            };
+            let apply = diff
+                .apply
+                .map(|apply| quote!({ let target = #target; #apply }));
+            FieldDiff { apply, ..diff }
+        };
+        let arm = match (attrs.ptr_eq, pointer) {
+            (false, _) => diff.arm,
+            (true, Pointer::Rc) => {
+                let arm = &diff.arm;
+                quote!(if !::std::rc::Rc::ptr_eq(#old, #new) #arm)
+            }
+            (true, Pointer::Arc) => {
+                let arm = &diff.arm;
+                quote!(if !::std::sync::Arc::ptr_eq(#old, #new) #arm)
             }
+            (true, _) => {
+                return Err(syn::Error::new(
+                    span,
+                    "`ptr_eq` only applies to `Rc` / `Arc` fields",
+                ));
+            }
+        };
+        return Ok(FieldDiff { arm, ..diff });
+    }
+    if attrs.ptr_eq {
+        return Err(syn::Error::new(
+            span,
+            "`ptr_eq` only applies to `Rc` / `Arc` fields",
+        ));
+    }
+
+    /* Vec<T>: matched by key, through an LCS, or by hash */
+    if let Some(Container::Vec {
+        elem: elem_ty,
+        deque,
+    }) = container_kind(ty)
+    {
+        not_scalar()?;
+        if deque && (attrs.algorithm.is_some() || attrs.ranges) {
+            return Err(syn::Error::new(
+                span,
+                "`algorithm` and `ranges` need a contiguous `Vec`, not a `VecDeque`",
+            ));
         }
-        "HashMap" => {
-            if let PathArguments::AngleBracketed(ABGA { args, .. }) = &seg.arguments {
-                let mut it = args.iter();
-                if let (Some(GenericArgument::Type(k)), Some(GenericArgument::Type(v))) =
-                    (it.next(), it.next())
-                {
-                    return Some(Container::Map(k, v));
+        let vec = VecDiff {
+            elem_ty,
+            span,
+            old,
+            new,
+            wrap,
+            ranges: attrs.ranges,
+        };
+        return match (&attrs.key, attrs.algorithm) {
+            (Some(_), Some(_)) => Err(syn::Error::new(
+                span,
+                "`algorithm` cannot be combined with `key` / `key_fn`",
+            )),
+            (Some(key), None) => Ok(keyed_vec_diff(ctx, vec, key)),
+            (None, None | Some(VecAlgorithm::Hash)) => Ok(hash_vec_diff(ctx, vec)),
+            (None, Some(algorithm)) => Ok(lcs_vec_diff(ctx, vec, algorithm)),
+        };
+    }
+    if attrs.key.is_some() || attrs.algorithm.is_some() || attrs.ranges {
+        return Err(syn::Error::new(
+            span,
+            "`key`, `key_fn`, `algorithm` and `ranges` only apply to `Vec` / `VecDeque` fields",
+        ));
+    }
+
+    /* container fields */
+    if let Some(kind) = container_kind(ty) {
+        not_scalar()?;
+        return Ok(match kind {
+            Container::Vec { .. } => unreachable!("handled above"),
+
+            /* Option<T> */
+            Container::Option(inner) => {
+                let set = wrap(quote!(::differs::OptionChanged::Set(nv)));
+                let unset = wrap(quote!(::differs::OptionChanged::Unset(ov)));
+
+                /* both `Some`: nested changes, or a plain re-`Set` for scalars */
+                ctx.bound(inner, quote!(::differs::HasChanges));
+                let inner_change = wrap(quote!(::differs::OptionChanged::Inner(c)));
+                let ty = quote_spanned!(span=> ::differs::OptionChanged<#lt,#inner,<#inner as ::differs::HasChanges>::Change<#lt>>);
+                let both = quote!({
+                    let mut _subs = Vec::new();
+                    <#inner as ::differs::HasChanges>::collect_changes(ov, nv, &mut _subs);
+                    if !<#inner as ::differs::HasChanges>::SCALAR {
+                        out.extend(_subs.into_iter().map(|c| #inner_change));
+                    } else if !_subs.is_empty() {
+                        out.push(#set);
+                    }
+                });
+                let nested =
+                    quote!(|v, subs| <#inner as ::differs::ApplyChanges>::apply_changes(v, subs));
+
+                FieldDiff {
+                    pair: false,
+                    apply: Some(
+                        quote!(::differs::__private::apply_option(target, changes, #nested)),
+                    ),
+                    ty,
+                    arm: quote_spanned!(span=>{
+                        match (#old, #new) {
+                            (None, Some(nv)) => out.push(#set),
+                            (Some(ov), None) => out.push(#unset),
+                            (Some(ov), Some(nv)) => #both,
+                            (None, None) => {}
+                        }
+                    }),
+                }
+            }
+
+            /* HashSet<T> / IndexSet<T> / BTreeSet<T> */
+            Container::Set(elem_ty, order) => {
+                let removed = wrap(quote!(::differs::Changed::Removed(v)));
+                let added = wrap(quote!(::differs::Changed::Added(v)));
+                let walk = if order == Order::Sorted {
+                    ctx.bound(elem_ty, quote!(Ord));
+                    /* one pass in ascending order */
+                    quote! {
+                        for v in old_v.symmetric_difference(new_v) {
+                            if old_v.contains(v) {
+                                out.push(#removed);
+                            } else {
+                                out.push(#added);
+                            }
+                        }
+                    }
+                } else {
+                    ctx.bound(elem_ty, quote!(::std::hash::Hash + Eq));
+                    quote! {
+                        for v in old_v.difference(new_v) {
+                            out.push(#removed);
+                        }
+                        for v in new_v.difference(old_v) {
+                            out.push(#added);
+                        }
+                    }
+                };
+
+                FieldDiff {
+                    pair: false,
+                    apply: Some(quote!(::differs::__private::apply_set(target, changes))),
+                    ty: quote_spanned!(span=> ::differs::Changed<#lt,#elem_ty>),
+                    arm: quote_spanned!(span=>{
+                        let old_v = #old;
+                        let new_v = #new;
+                        #walk
+                    }),
+                }
+            }
+
+            /* HashMap<K,V> / IndexMap<K,V> / BTreeMap<K,V> */
+            Container::Map(k, v, order) => {
+                let removed = wrap(quote!(::differs::MapChanged::RemovedEntry(k, ov)));
+                let added = wrap(quote!(::differs::MapChanged::AddedEntry(k, nv)));
+
+                /* scalar values are replaced, others report their nested changes */
+                ctx.bound(v, quote!(::differs::HasChanges));
+                let modified = wrap(quote!(::differs::MapChanged::ModifiedEntry(k, subs)));
+                let replaced = wrap(quote!(::differs::MapChanged::ChangedEntry(k, ov, nv)));
+                let ty = quote_spanned!(span=> ::differs::MapChanged<#lt,#k,#v,<#v as ::differs::HasChanges>::Change<#lt>>);
+                let changed = quote!({
+                    let subs = ::differs::diff_changes(ov, nv);
+                    if subs.is_empty() {
+                        /* unchanged */
+                    } else if <#v as ::differs::HasChanges>::SCALAR {
+                        out.push(#replaced);
+                    } else {
+                        out.push(#modified);
+                    }
+                });
+                let nested =
+                    quote!(|v, subs| <#v as ::differs::ApplyChanges>::apply_changes(v, subs));
+
+                let walk = if order == Order::Sorted {
+                    ctx.bound(k, quote!(Ord));
+                    /* merged walk over both key sets, in ascending order */
+                    quote! {
+                        let keys: ::std::collections::BTreeSet<&#k> =
+                            old_v.keys().chain(new_v.keys()).collect();
+                        for k in keys {
+                            match (old_v.get(k), new_v.get(k)) {
+                                (Some(ov), None) => out.push(#removed),
+                                (None, Some(nv)) => out.push(#added),
+                                (Some(ov), Some(nv)) => #changed,
+                                (None, None) => {}
+                            }
+                        }
+                    }
+                } else {
+                    ctx.bound(k, quote!(::std::hash::Hash + Eq));
+                    quote! {
+                        /* removals + modifications */
+                        for (k,ov) in old_v {
+                            match new_v.get(k) {
+                                None => out.push(#removed),
+                                Some(nv) => #changed,
+                            }
+                        }
+                        /* pure additions */
+                        for (k,nv) in new_v {
+                            if !old_v.contains_key(k) {
+                                out.push(#added);
+                            }
+                        }
+                    }
+                };
+
+                FieldDiff {
+                    pair: false,
+                    apply: Some(quote!(::differs::__private::apply_map(target, changes, #nested))),
+                    ty,
+                    arm: quote_spanned!(span=>{
+                        let old_v = #old;
+                        let new_v = #new;
+                        #walk
+                    }),
+                }
+            }
+        });
+    }
+
+    /* [T; N]: positional */
+    if let Type::Array(arr) = ty {
+        not_scalar()?;
+        let elem_ty = &*arr.elem;
+        ctx.bound(elem_ty, quote!(::differs::HasChanges));
+        let modified = wrap(quote!(::differs::Changed::ModifiedAt(i, nv, subs)));
+        let ty = quote_spanned!(span=> ::differs::Changed<#lt,#elem_ty,<#elem_ty as ::differs::HasChanges>::Change<#lt>>);
+        let check = quote!({
+            let mut subs = ::differs::diff_changes(ov, nv);
+            if !subs.is_empty() {
+                /* replaced scalars carry no nested changes */
+                if <#elem_ty as ::differs::HasChanges>::SCALAR {
+                    subs.clear();
+                }
+                out.push(#modified);
+            }
+        });
+        return Ok(FieldDiff {
+            pair: false,
+            apply: Some(quote!(::differs::__private::apply_array(target, changes))),
+            ty,
+            arm: quote_spanned!(span=>{
+                let old_v = #old;
+                let new_v = #new;
+                for (i, (ov, nv)) in old_v.iter().zip(new_v).enumerate() {
+                    #check
                 }
+            }),
+        });
+    }
+
+    /* (A, B, ..): element-wise, `item0`, `item1`, … */
+    if let Type::Tuple(tuple) = ty {
+        if !tuple.elems.is_empty() {
+            not_scalar()?;
+            if tuple.elems.len() > 12 {
+                return Err(syn::Error::new(
+                    span,
+                    "tuples with more than 12 elements are not supported",
+                ));
+            }
+            let mut payloads = Vec::new();
+            let mut arms = Vec::new();
+            let mut applies = Some(Vec::new());
+            for (idx, elem_ty) in tuple.elems.iter().enumerate() {
+                let item = format_ident!("item{}", idx);
+                let idx = syn::Index::from(idx);
+                /* parenthesised so an `old_new` pair stays one payload */
+                let diff = field_diff(
+                    ctx,
+                    elem_ty,
+                    &FieldAttrs::default(),
+                    span,
+                    quote!(&(#old).#idx),
+                    quote!(&(#new).#idx),
+                    &|c| wrap(quote!(::differs::TupleChanged::#item((#c)))),
+                )?;
+                let payload = diff.payload();
+                payloads.push(quote!((#(#payload),*)));
+                arms.push(diff.arm);
+                let pick = if diff.pair {
+                    quote!(::differs::TupleChanged::#item((o, n)) => Some((o, n)))
+                } else {
+                    quote!(::differs::TupleChanged::#item(c) => Some(c))
+                };
+                let segment = idx.index.to_string();
+                applies = applies.zip(diff.apply).map(|(mut applies, apply)| {
+                    applies.push(quote! {
+                        .and_then(|()| ::differs::__private::apply_part(
+                            &changes,
+                            |c| match c { #pick, _ => None },
+                            #segment,
+                            |changes| { let target = &mut target.#idx; #apply },
+                        ))
+                    });
+                    applies
+                });
             }
+            return Ok(FieldDiff {
+                pair: false,
+                apply: applies.map(|applies| quote!(Ok(()) #(#applies)*)),
+                ty: quote_spanned!(span=> ::differs::TupleChanged<#(#payloads),*>),
+                arm: quote!({ #(#arms)* }),
+            });
         }
-        _ => {}
     }
-    None
+
+    /* structs, enums, type parameters and std scalars alike */
+    if through_trait(ty, pair) {
+        return Ok(trait_diff(ctx, ty, span, old, new, wrap));
+    }
+
+    /* `old_new`, or a non-path type */
+    Ok(scalar_diff(ctx, ty, None, pair, span, old, new, wrap))
+}
+
+/// Field diffed by its `HasChanges` impl, which also decides whether it is
+/// a scalar (one change carrying the new value) or reports nested changes.
+fn trait_diff(
+    ctx: &mut Ctx,
+    ty: &Type,
+    span: Span,
+    old: TokenStream2,
+    new: TokenStream2,
+    wrap: &dyn Fn(TokenStream2) -> TokenStream2,
+) -> FieldDiff {
+    ctx.bound(ty, quote!(::differs::HasChanges));
+    let lt = &ctx.lt;
+    let nested = wrap(quote!(c));
+    FieldDiff {
+        pair: false,
+        apply: Some(quote!(<#ty as ::differs::ApplyChanges>::apply_changes(target, changes))),
+        ty: quote_spanned!(span=> <#ty as ::differs::HasChanges>::Change<#lt>),
+        arm: quote_spanned!(span=>{
+            let mut _subs = Vec::new();
+            <#ty as ::differs::HasChanges>::collect_changes(#old,#new,&mut _subs);
+            out.extend(_subs.into_iter().map(|c| #nested));
+        }),
+    }
+}
+
+/// `true` when both sides count as equal: the field's own `eq`, or `==`.
+fn field_eq(
+    ctx: &mut Ctx,
+    ty: &Type,
+    eq: Option<&FieldEq>,
+    old: TokenStream2,
+    new: TokenStream2,
+) -> TokenStream2 {
+    match eq {
+        Some(FieldEq::Fn(path)) => quote!(#path(#old, #new)),
+        Some(FieldEq::Changed(path)) => quote!(!#path(#old, #new)),
+        Some(FieldEq::Epsilon(epsilon)) => {
+            ctx.bound(ty, quote!(::differs::FloatEq));
+            quote!(::differs::eq_epsilon(#old, #new, #epsilon))
+        }
+        Some(FieldEq::Ulps(ulps)) => {
+            ctx.bound(ty, quote!(::differs::FloatEq));
+            quote!(::differs::eq_ulps(#old, #new, #ulps))
+        }
+        None => {
+            ctx.bound(ty, quote!(PartialEq));
+            quote!(#old == #new)
+        }
+    }
+}
+
+/// Atomic field compared with `PartialEq` (or its own `eq`); `pair` reports
+/// `(old, new)` instead of the new value.
+#[allow(clippy::too_many_arguments)]
+fn scalar_diff(
+    ctx: &mut Ctx,
+    ty: &Type,
+    eq: Option<&FieldEq>,
+    pair: bool,
+    span: Span,
+    old: TokenStream2,
+    new: TokenStream2,
+    wrap: &dyn Fn(TokenStream2) -> TokenStream2,
+) -> FieldDiff {
+    let lt = ctx.lt.clone();
+    let same = field_eq(ctx, ty, eq, quote!(old_v), quote!(new_v));
+    let changed = if pair {
+        wrap(quote!(old_v, new_v))
+    } else {
+        wrap(quote!(new_v))
+    };
+    let apply = if pair {
+        quote!(::differs::__private::apply_old_new(target, changes, |old_v, new_v| #same))
+    } else {
+        quote!(::differs::__private::apply_value(target, changes))
+    };
+    FieldDiff {
+        pair,
+        apply: Some(apply),
+        ty: quote_spanned!(span=> &#lt #ty),
+        arm: quote_spanned!(span=>{
+            let old_v = #old;
+            let new_v = #new;
+            if !(#same) {
+                out.push(#changed);
+            }
+        }),
+    }
+}
+
+/// Inputs shared by the `Vec<T>` field generators.
+struct VecDiff<'f> {
+    elem_ty: &'f Type,
+    span: Span,
+    /// `&'a Vec<T>` expressions.
+    old: TokenStream2,
+    new: TokenStream2,
+    wrap: &'f dyn Fn(TokenStream2) -> TokenStream2,
+    /// `#[differs(ranges)]`
+    ranges: bool,
+}
+
+impl VecDiff<'_> {
+    /// `Changed` payload, carrying the elements' own change type.
+    fn change_ty(&self, lt: &Lifetime) -> TokenStream2 {
+        let (elem_ty, span) = (self.elem_ty, self.span);
+        quote_spanned!(span=> ::differs::Changed<#lt,#elem_ty,<#elem_ty as ::differs::HasChanges>::Change<#lt>>)
+    }
+
+    /// Rebuilds `target` from the patch; removed and moved elements must be
+    /// `same` (a closure over two element refs) as the recorded ones.
+    fn apply(&self, same: TokenStream2) -> Option<TokenStream2> {
+        Some(quote! {
+            ::differs::__private::patched_vec(target.iter(), changes, #same)
+                .map(|v| *target = v.into_iter().collect())
+        })
+    }
+
+    /// Wraps the local `changes` and moves them onto `out`.
+    fn finish(&self) -> TokenStream2 {
+        let change = (self.wrap)(quote!(c));
+        let coalesce = self
+            .ranges
+            .then(|| quote!(let changes = ::differs::coalesce_ranges(old_v, new_v, changes);));
+        quote! {
+            #coalesce
+            out.extend(changes.into_iter().map(|c| #change));
+        }
+    }
+}
+
+/// Plain `Vec<T>` field: value → index-queue matching (`T: Hash + Eq`).
+fn hash_vec_diff(ctx: &mut Ctx, vec: VecDiff) -> FieldDiff {
+    let (elem_ty, span) = (vec.elem_ty, vec.span);
+    let (old, new) = (&vec.old, &vec.new);
+    ctx.bound(elem_ty, quote!(::std::hash::Hash + Eq));
+
+    /* non-scalar elements get paired up and report `ModifiedAt` */
+    ctx.bound(elem_ty, quote!(::differs::HasChanges));
+    let scalar = quote!(<#elem_ty as ::differs::HasChanges>::SCALAR);
+    let finish = vec.finish();
+
+    FieldDiff {
+        pair: false,
+        apply: vec.apply(quote!(|a, b| a == b)),
+        ty: vec.change_ty(&ctx.lt),
+        arm: quote_spanned!(span=>{
+            use std::collections::{HashMap, HashSet};
+
+            let old_v = #old;
+            let new_v = #new;
+            let mut changes = Vec::new();
+
+            /* map value -> queue of old indices  */
+            let mut idx_map: HashMap<&#elem_ty, Vec<usize>> = HashMap::new();
+            for (i,v) in old_v.iter().enumerate() {
+                idx_map.entry(v).or_default().push(i);
+            }
+
+            /* pass 1 – match every NEW element to an identical OLD one */
+            let matched: Vec<Option<usize>> = new_v
+                .iter()
+                .map(|val| idx_map.get_mut(val).and_then(|vec| vec.pop()))
+                .collect();
+
+            /* which old indices were re-used (= kept/moved/modified) */
+            let mut reused_old : HashSet<usize> =
+                matched.iter().flatten().copied().collect();
+
+            /* occurrences of each value seen so far */
+            let mut seen: HashMap<&#elem_ty, usize> = HashMap::new();
+
+            /* pass 2 – walk the NEW vector and classify */
+            for (new_idx, val) in new_v.iter().enumerate() {
+                let nth = seen.entry(val).or_insert(0);
+                *nth += 1;
+                let nth = *nth - 1;
+
+                match matched[new_idx] {
+                    /* identical element existed before */
+                    Some(old_idx) => {
+                        if old_idx != new_idx {
+                            changes.push(::differs::Changed::Moved(val, old_idx, new_idx));
+                        }
+                        /* equal, but possibly not identical */
+                        if !#scalar {
+                            let subs = ::differs::diff_changes(&old_v[old_idx], val);
+                            if !subs.is_empty() {
+                                changes.push(::differs::Changed::ModifiedAt(new_idx, val, subs));
+                            }
+                        }
+                    }
+                    /* unmatched on both sides at the same index; values unequal
+                     * only in what the diff does not see stay an add + remove */
+                    None if !#scalar && new_idx < old_v.len() && !reused_old.contains(&new_idx) => {
+                        let subs = ::differs::diff_changes(&old_v[new_idx], val);
+                        if subs.is_empty() {
+                            changes.push(::differs::Changed::AddedAt(new_idx, val, nth));
+                        } else {
+                            reused_old.insert(new_idx);
+                            changes.push(::differs::Changed::ModifiedAt(new_idx, val, subs));
+                        }
+                    }
+                    /* entirely new value */
+                    None => {
+                        changes.push(::differs::Changed::AddedAt(new_idx, val, nth));
+                    }
+                }
+            }
+
+            /* pass 3 – any old indices NOT re-used are removals */
+            seen.clear();
+            for (old_idx, val) in old_v.iter().enumerate() {
+                let nth = seen.entry(val).or_insert(0);
+                *nth += 1;
+                if !reused_old.contains(&old_idx) {
+                    changes.push(::differs::Changed::RemovedAt(old_idx, val, *nth - 1));
+                }
+            }
+
+            #finish
+        }),
+    }
+}
+
+/// `Vec<T>` field with `#[differs(algorithm = "myers" | "patience")]`:
+/// the LCS is computed at runtime by the library.
+fn lcs_vec_diff(ctx: &mut Ctx, vec: VecDiff, algorithm: VecAlgorithm) -> FieldDiff {
+    let (elem_ty, span) = (vec.elem_ty, vec.span);
+    let (old, new) = (&vec.old, &vec.new);
+    ctx.bound(elem_ty, quote!(PartialEq + ::differs::HasChanges));
+    /* equal elements are grouped by `EQ_HASH` where the type has one */
+    let hash = quote!(<#elem_ty as ::differs::HasChanges>::EQ_HASH);
+    let lcs = match algorithm {
+        VecAlgorithm::Patience => quote!(::differs::patience_lcs(old_v, new_v, #hash)),
+        _ => quote!(::differs::myers_lcs(old_v, new_v)),
+    };
+    /* scalar elements are replaced, never modified in place */
+    let nested = quote!((!<#elem_ty as ::differs::HasChanges>::SCALAR)
+        .then_some(::differs::diff_changes::<#elem_ty> as fn(_, _) -> _));
+    let finish = vec.finish();
+
+    FieldDiff {
+        pair: false,
+        apply: vec.apply(quote!(|a, b| a == b)),
+        ty: vec.change_ty(&ctx.lt),
+        arm: quote_spanned!(span=>{
+            let old_v = #old;
+            let new_v = #new;
+            let matched = #lcs;
+            let changes = ::differs::ordered_changes(old_v, new_v, &matched, #nested, #hash);
+            #finish
+        }),
+    }
+}
+
+/// `Vec<T>` field with `#[differs(key = ..)]` / `#[differs(key_fn = ..)]`:
+/// elements are paired by key and paired elements are diffed in place.
+fn keyed_vec_diff(ctx: &mut Ctx, vec: VecDiff, key: &VecKey) -> FieldDiff {
+    let (elem_ty, span) = (vec.elem_ty, vec.span);
+    let (old, new) = (&vec.old, &vec.new);
+    let lt = &ctx.lt;
+    let (key_of, same) = match key {
+        VecKey::Field(member) => (
+            quote_spanned!(span=> |v: &#lt #elem_ty| &v.#member),
+            quote_spanned!(span=> |a: &#elem_ty, b: &#elem_ty| a.#member == b.#member),
+        ),
+        VecKey::Fn(path) => (
+            quote_spanned!(span=> |v: &#lt #elem_ty| #path(v)),
+            quote_spanned!(span=> |a: &#elem_ty, b: &#elem_ty| #path(a) == #path(b)),
+        ),
+    };
+    ctx.bound(elem_ty, quote!(::differs::HasChanges));
+    let finish = vec.finish();
+
+    FieldDiff {
+        pair: false,
+        apply: vec.apply(same),
+        ty: vec.change_ty(&ctx.lt),
+        arm: quote_spanned!(span=>{
+            use std::collections::{HashMap, HashSet};
+
+            let old_v = #old;
+            let new_v = #new;
+            let key_of = #key_of;
+            let mut changes = Vec::new();
+
+            /* key -> first old index carrying it */
+            let mut idx_map = HashMap::new();
+            for (i,v) in old_v.iter().enumerate() {
+                idx_map.entry(key_of(v)).or_insert(i);
+            }
+
+            /* which old indices were paired with a new element */
+            let mut reused_old : HashSet<usize> = HashSet::new();
+
+            /* occurrences of each key seen so far */
+            let mut seen = HashMap::new();
+
+            /* pass 1 – walk the NEW vector and pair by key */
+            for (new_idx, val) in new_v.iter().enumerate() {
+                let nth = seen.entry(key_of(val)).or_insert(0);
+                *nth += 1;
+                let nth = *nth - 1;
+
+                match idx_map.remove(&key_of(val)) {
+                    Some(old_idx) => {
+                        reused_old.insert(old_idx);
+
+                        if old_idx != new_idx {
+                            changes.push(::differs::Changed::Moved(val, old_idx, new_idx));
+                        }
+                        let subs = ::differs::diff_changes(&old_v[old_idx], val);
+                        if !subs.is_empty() {
+                            changes.push(::differs::Changed::ModifiedAt(new_idx, val, subs));
+                        }
+                    }
+                    /* key not seen before (or already paired) */
+                    None => {
+                        changes.push(::differs::Changed::AddedAt(new_idx, val, nth));
+                    }
+                }
+            }
+
+            /* pass 2 – any old indices NOT paired are removals */
+            seen.clear();
+            for (old_idx, val) in old_v.iter().enumerate() {
+                let nth = seen.entry(key_of(val)).or_insert(0);
+                *nth += 1;
+                if !reused_old.contains(&old_idx) {
+                    changes.push(::differs::Changed::RemovedAt(old_idx, val, *nth - 1));
+                }
+            }
+
+            #finish
+        }),
+    }
 }
 
 /* ------------------------------------------------------------------------- */
@@ -95,251 +1020,1458 @@ pub fn derive_diff_impl(input: TokenStream) -> TokenStream {
         ident,
         data,
         generics,
+        attrs,
         ..
     } = parse_macro_input!(input as DeriveInput);
 
-    let Data::Struct(ds) = data else {
-        return syn::Error::new_spanned(ident, "Diff can only be derived for structs")
-            .to_compile_error()
-            .into();
+    let expanded = ContainerAttrs::parse(&attrs).and_then(|attrs| {
+        let expanded = match data {
+            Data::Struct(ds) => derive_struct(&ident, &generics, &attrs, ds),
+            Data::Enum(de) => derive_enum(&ident, &generics, &attrs, de),
+            Data::Union(_) => Err(syn::Error::new_spanned(
+                &ident,
+                "Diff cannot be derived for unions",
+            )),
+        }?;
+        /* one clear error, rather than unresolved `__private::serde` paths,
+         * when the `serde` feature of differs is off */
+        Ok(if attrs.serde {
+            quote_spanned!(ident.span()=> ::differs::__require_serde! { #expanded })
+        } else {
+            expanded
+        })
+    });
+
+    TokenStream::from(expanded.unwrap_or_else(syn::Error::into_compile_error))
+}
+
+/* ------------------------------------------------------------------------- */
+/* JSON Patch export (`#[differs(serde)]`)                                   */
+/* ------------------------------------------------------------------------- */
+
+/// JSON Pointer segment of field `idx` in serde's form of `fields`: named
+/// fields by name, tuple bodies as arrays, newtypes transparent.
+fn json_segment(fields: &Fields, idx: usize) -> String {
+    match fields.iter().nth(idx).and_then(|f| f.ident.as_ref()) {
+        Some(fid) => format!("/{}", fid.unraw()),
+        None if fields.len() == 1 => String::new(),
+        None => format!("/{idx}"),
+    }
+}
+
+/// Writes the changes of one field, picked out of `changes` by `variant`,
+/// below `at` + `segment`. An `old_new` pair is written by its new side.
+fn json_field(variant: TokenStream2, pair: bool, segment: String) -> TokenStream2 {
+    let pick = if pair {
+        quote!(#variant(_, c) => Some(c))
+    } else {
+        quote!(#variant(c) => Some(c))
     };
-    let Fields::Named(fields) = ds.fields else {
-        return syn::Error::new_spanned(ident, "Diff needs named fields")
-            .to_compile_error()
-            .into();
+    quote! {
+        ::differs::__private::json_part(
+            changes,
+            |c| match c { #pick, _ => None },
+            format!("{}{}", at, #segment),
+            out,
+        )?;
+    }
+}
+
+/// Change-type generics plus `ty: bound` for every pair in `bounds` whose
+/// payload type depends on a type parameter.
+fn bounded_generics(
+    ctx: &Ctx,
+    change_generics: &Generics,
+    bounds: Vec<(TokenStream2, TokenStream2)>,
+) -> syn::Result<Generics> {
+    let mut generics = change_generics.clone();
+    let where_clause = generics.make_where_clause();
+    for (ty, bound) in bounds {
+        if ctx.is_generic(&syn::parse2(ty.clone())?) {
+            where_clause.predicates.push(parse_quote!(#ty: #bound));
+        }
+    }
+    Ok(generics)
+}
+
+/// `impl JsonPatchOps` for the generated change enum `enum_ident`; `body`
+/// writes `changes: &[&Self]` below the pointer `at` to `out`. `bounds`
+/// pairs payload types with the trait they need.
+fn json_patch_impl(
+    ctx: &Ctx,
+    change_generics: &Generics,
+    enum_ident: &Ident,
+    bounds: Vec<(TokenStream2, TokenStream2)>,
+    body: TokenStream2,
+) -> syn::Result<TokenStream2> {
+    let generics = bounded_generics(ctx, change_generics, bounds)?;
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let json = quote!(::differs::__private::serde_json);
+    Ok(quote! {
+        impl #impl_generics ::differs::JsonPatchOps for #enum_ident #ty_generics #where_clause {
+            /* field-less types never look at the changes */
+            #[allow(unreachable_patterns, unused_variables)]
+            fn json_patch_ops(
+                changes: &[&Self],
+                at: &str,
+                out: &mut Vec<#json::Value>,
+            ) -> ::std::result::Result<(), #json::Error> {
+                #body
+                Ok(())
+            }
+        }
+    })
+}
+
+/// Bounds for [`json_patch_impl`]: every payload of `shapes` writes itself.
+fn json_bounds<'s>(
+    shapes: impl IntoIterator<Item = &'s Shape>,
+) -> Vec<(TokenStream2, TokenStream2)> {
+    shapes
+        .into_iter()
+        .flat_map(Shape::types)
+        .map(|ty| (ty.clone(), quote!(::differs::JsonPatchOps)))
+        .collect()
+}
+
+/* ------------------------------------------------------------------------- */
+/* Change paths                                                              */
+/* ------------------------------------------------------------------------- */
+
+/// Path and `vacated_below` arms for the change variant `variant` of the
+/// field `name`: nested payloads extend the field's path, `old_new` pairs
+/// and `with_module` payloads stop at it and never move anything.
+fn path_arm(variant: TokenStream2, name: String, nested: bool) -> (TokenStream2, TokenStream2) {
+    if nested {
+        (
+            quote!(#variant(c) => ::differs::ChangePath::path_below(c, &at.child(#name))),
+            quote!(#variant(c) => ::differs::ChangePath::vacated_below(c, &at.child(#name), out)),
+        )
+    } else {
+        (
+            quote!(#variant(..) => at.child(#name)),
+            quote!(#variant(..) => {}),
+        )
+    }
+}
+
+/// `impl ChangePath` for the generated change enum `enum_ident`, matching
+/// `arms`; `nested` lists the payload types the arms descend into.
+fn path_impl(
+    ctx: &Ctx,
+    change_generics: &Generics,
+    enum_ident: &Ident,
+    nested: Vec<TokenStream2>,
+    arms: Vec<(TokenStream2, TokenStream2)>,
+) -> syn::Result<TokenStream2> {
+    let (arms, vacated): (Vec<_>, Vec<_>) = arms.into_iter().unzip();
+    let bounds = nested
+        .into_iter()
+        .map(|ty| (ty, quote!(::differs::ChangePath)))
+        .collect();
+    let generics = bounded_generics(ctx, change_generics, bounds)?;
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::differs::ChangePath for #enum_ident #ty_generics #where_clause {
+            #[allow(unreachable_patterns)]
+            fn path_below(&self, at: &::differs::FieldName) -> ::differs::FieldName {
+                match self {
+                    #( #arms, )*
+                    /* `self_` / `VariantChanged`: the value itself */
+                    _ => ::std::clone::Clone::clone(at),
+                }
+            }
+
+            #[allow(unreachable_patterns)]
+            fn vacated_below(&self, at: &::differs::FieldName, out: &mut Vec<::differs::FieldName>) {
+                match self {
+                    #( #vacated, )*
+                    _ => {}
+                }
+            }
+        }
+    })
+}
+
+/* ------------------------------------------------------------------------- */
+/* `changed!` accessors                                                      */
+/* ------------------------------------------------------------------------- */
+
+/// Hidden `__changed_<variant>` accessors on the change enum `enum_ident`.
+/// Below its first level `changed!` cannot name the nested change type
+/// (`<Enum><Variant>Change`, or a field type it never sees), so it descends
+/// by method call instead. Variants with named fields get none.
+fn changed_accessors(
+    change_generics: &Generics,
+    enum_ident: &Ident,
+    variants: &[(Ident, Shape)],
+) -> TokenStream2 {
+    let (impl_generics, ty_generics, where_clause) = change_generics.split_for_impl();
+    let methods = variants.iter().filter_map(|(variant, shape)| {
+        let Shape::Unnamed(tys) = shape else {
+            return None;
+        };
+        let method = format_ident!("__changed_{}", variant.unraw(), span = variant.span());
+        let binds: Vec<_> = (0..tys.len()).map(|idx| format_ident!("__{idx}")).collect();
+        let (ret, value) = match &tys[..] {
+            [ty] => (quote!(&#ty), quote!(#(#binds)*)),
+            _ => (quote!((#(&#tys),*)), quote!((#(#binds),*))),
+        };
+        Some(quote! {
+            #[doc(hidden)]
+            #[allow(non_snake_case, unreachable_patterns)]
+            pub fn #method(&self) -> ::std::option::Option<#ret> {
+                match self {
+                    Self::#variant(#(#binds),*) => ::std::option::Option::Some(#value),
+                    _ => ::std::option::Option::None,
+                }
+            }
+        })
+    });
+    quote! {
+        impl #impl_generics #enum_ident #ty_generics #where_clause {
+            #(#methods)*
+        }
+    }
+}
+
+/* ------------------------------------------------------------------------- */
+/* Struct handling                                                           */
+/* ------------------------------------------------------------------------- */
+
+/// `self_` variant + `collect_changes` body around the per-field `diff`:
+/// `self_` leads the changes whenever any were emitted, unless the type opts
+/// out with `#[differs(no_self)]`.
+fn self_change(
+    attrs: &ContainerAttrs,
+    enum_ident: &Ident,
+    snapshot_ident: &Ident,
+    change_ty: &syn::TypeGenerics,
+    diff: TokenStream2,
+) -> (TokenStream2, TokenStream2) {
+    if attrs.no_self {
+        return (quote!(), diff);
+    }
+    (
+        quote!(self_(#snapshot_ident #change_ty),),
+        quote! {
+            let start = out.len();
+            #diff
+            if out.len() > start {
+                out.insert(start, #enum_ident::self_(#snapshot_ident::from(new)));
+            }
+        },
+    )
+}
+
+/* ------------------------------------------------------------------------- */
+/* Owned mirrors (`#[differs(owned)]`)                                       */
+/* ------------------------------------------------------------------------- */
+
+/// Payload types of one generated struct body / enum variant, borrowing
+/// `ctx.lt`.
+enum Shape {
+    Named(Vec<(Ident, TokenStream2)>),
+    /// Tuple body; also stands in for the borrowed unit-struct snapshot.
+    Unnamed(Vec<TokenStream2>),
+    Unit,
+}
+
+impl Shape {
+    fn types(&self) -> Vec<&TokenStream2> {
+        match self {
+            Shape::Named(fields) => fields.iter().map(|(_, ty)| ty).collect(),
+            Shape::Unnamed(tys) => tys.iter().collect(),
+            Shape::Unit => Vec::new(),
+        }
+    }
+}
+
+/// A generated snapshot / change type to mirror.
+struct Mirror {
+    ident: Ident,
+    body: Body,
+    /// The borrowed enum carries a hidden `__phantom` variant.
+    phantom: bool,
+}
+
+enum Body {
+    Struct(Shape),
+    Enum(Vec<(Ident, Shape)>),
+}
+
+impl Mirror {
+    fn shapes(&self) -> Vec<&Shape> {
+        match &self.body {
+            Body::Struct(shape) => vec![shape],
+            Body::Enum(variants) => variants.iter().map(|(_, shape)| shape).collect(),
+        }
+    }
+}
+
+/// `Debug` for a generated borrowed type. `#[derive(Debug)]` would need every
+/// payload that depends on a type parameter to be `Debug` wherever the type
+/// is named; this impl asks for it only when formatting.
+fn debug_impl(ctx: &Ctx, generics: &Generics, mirror: &Mirror) -> syn::Result<TokenStream2> {
+    let ident = &mirror.ident;
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let mut preds: Vec<TokenStream2> = where_clause
+        .into_iter()
+        .flat_map(|w| &w.predicates)
+        .map(ToTokens::to_token_stream)
+        .collect();
+    let mut seen = HashSet::new();
+    for ty in mirror.shapes().into_iter().flat_map(Shape::types) {
+        if ctx.is_generic(&syn::parse2(ty.clone())?) && seen.insert(ty.to_string()) {
+            preds.push(quote!(#ty: ::std::fmt::Debug));
+        }
+    }
+
+    /* `..` skips the hidden `PhantomData` the shapes leave out */
+    let fmt = |path: TokenStream2, name: String, shape: &Shape| match shape {
+        Shape::Named(fields) => {
+            let names: Vec<_> = fields.iter().map(|(name, _)| name).collect();
+            let labels = names.iter().map(|name| name.unraw().to_string());
+            quote! {
+                #path { #( #names, )* .. } => f.debug_struct(#name)
+                    #( .field(#labels, #names) )*
+                    .finish()
+            }
+        }
+        Shape::Unnamed(tys) => {
+            let binds: Vec<_> = (0..tys.len()).map(|i| format_ident!("__{}", i)).collect();
+            quote! {
+                #path( #( #binds, )* .. ) => f.debug_tuple(#name)
+                    #( .field(#binds) )*
+                    .finish()
+            }
+        }
+        Shape::Unit => quote!(#path => f.write_str(#name)),
+    };
+    let arms = match &mirror.body {
+        Body::Struct(shape) => vec![fmt(quote!(Self), ident.to_string(), shape)],
+        Body::Enum(variants) => {
+            let mut arms: Vec<_> = variants
+                .iter()
+                .map(|(name, shape)| fmt(quote!(Self::#name), name.unraw().to_string(), shape))
+                .collect();
+            if mirror.phantom {
+                arms.push(quote!(Self::__phantom(never, _) => match *never {}));
+            }
+            arms
+        }
     };
+    Ok(quote! {
+        impl #impl_generics ::std::fmt::Debug for #ident #ty_generics
+        where #( #preds, )*
+        {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                match self { #( #arms, )* }
+            }
+        }
+    })
+}
 
-    /* common idents */
-    let enum_ident = format_ident!("{ident}Change");
-    let snapshot_ident = format_ident!("{ident}Snapshot");
-    let (_, ty_generics, where_clause) = generics.split_for_impl();
-    let lt = quote!('a);
+/// `ty` with the borrow lifetime renamed to `'to` (e.g. `'static`).
+fn rename_lt(ctx: &Ctx, ty: &TokenStream2, to: &str) -> TokenStream2 {
+    fn walk(tokens: TokenStream2, lt: &Ident, to: &str) -> TokenStream2 {
+        let mut out = Vec::new();
+        let mut tick = false;
+        for tt in tokens {
+            let tt = match tt {
+                TokenTree::Ident(id) if tick && id == *lt => {
+                    TokenTree::Ident(Ident::new(to, id.span()))
+                }
+                TokenTree::Group(g) => {
+                    let mut group =
+                        proc_macro2::Group::new(g.delimiter(), walk(g.stream(), lt, to));
+                    group.set_span(g.span());
+                    TokenTree::Group(group)
+                }
+                tt => tt,
+            };
+            tick = matches!(&tt, TokenTree::Punct(p) if p.as_char() == '\'');
+            out.push(tt);
+        }
+        out.into_iter().collect()
+    }
+    walk(ty.clone(), &ctx.lt.ident, to)
+}
 
-    /* ------------------------------------------------------------------ */
-    /* Borrow-snapshot struct                                             */
-    /* ------------------------------------------------------------------ */
+/// Owned counterpart of payload `ty`: `<ty<'static> as IntoOwned>::Owned`.
+fn owned_ty(ctx: &Ctx, ty: &TokenStream2) -> TokenStream2 {
+    let st = rename_lt(ctx, ty, "static");
+    quote!(<#st as ::differs::IntoOwned>::Owned)
+}
+
+/// `#[differs(serde)]`: `Serialize` on a borrowed type, `Serialize` +
+/// `Deserialize` on an owned mirror. Bounded on the payloads that depend on
+/// a type parameter (serde's own inference would bound the parameters).
+fn serde_attrs<'s>(
+    ctx: &Ctx,
+    attrs: &ContainerAttrs,
+    shapes: impl IntoIterator<Item = &'s Shape>,
+    owned: bool,
+) -> syn::Result<TokenStream2> {
+    if !attrs.serde {
+        return Ok(quote!());
+    }
+    let serde = quote!(::differs::__private::serde);
+    let (mut ser, mut de) = (Vec::new(), Vec::new());
+    for ty in shapes.into_iter().flat_map(Shape::types) {
+        if !ctx.is_generic(&syn::parse2(ty.clone())?) {
+            continue;
+        }
+        let ty = if owned { owned_ty(ctx, ty) } else { ty.clone() };
+        ser.push(quote!(#ty: #serde::Serialize).to_string());
+        de.push(quote!(#ty: #serde::Deserialize<'de>).to_string());
+    }
+    let (ser, de) = (ser.join(", "), de.join(", "));
+    Ok(if owned {
+        quote! {
+            #[derive(#serde::Serialize, #serde::Deserialize)]
+            #[serde(crate = "::differs::__private::serde", bound(serialize = #ser, deserialize = #de))]
+        }
+    } else {
+        quote! {
+            #[derive(#serde::Serialize)]
+            #[serde(crate = "::differs::__private::serde", bound(serialize = #ser))]
+        }
+    })
+}
 
-    let (snap_fields, snap_init): (Vec<_>, Vec<_>) = fields
-        .named
+/// Keeps a hidden `__phantom` variant / field off the wire.
+fn serde_skip(attrs: &ContainerAttrs) -> TokenStream2 {
+    if attrs.serde {
+        quote!(#[serde(skip)])
+    } else {
+        quote!()
+    }
+}
+
+/// `<Name>…Owned` counterpart of a generated type + its `IntoOwned` impl.
+/// Every payload `P` becomes `<P<'static> as IntoOwned>::Owned`. The impl
+/// names the owned type of a payload that depends on a type parameter as an
+/// extra parameter `__O<n>`, bound for every lifetime at once: bounding
+/// `P<'a>` and `P<'static>` separately makes the two where clauses compete.
+fn owned_mirror(
+    ctx: &Ctx,
+    input: &Ident,
+    generics: &Generics,
+    attrs: &ContainerAttrs,
+    mirror: &Mirror,
+) -> syn::Result<TokenStream2> {
+    if let Some(lt) = generics.lifetimes().next() {
+        return Err(syn::Error::new_spanned(
+            lt,
+            "`owned` is not supported on types with lifetime parameters",
+        ));
+    }
+    let ident = &mirror.ident;
+    let owned_ident = format_ident!("{}Owned", ident);
+    let (_, ty_generics, _) = generics.split_for_impl();
+
+    /* bounds: `'static` type parameters, and owned payloads that depend on them */
+    let mut def_preds: Vec<TokenStream2> = ctx
+        .predicates(attrs)
         .iter()
-        .map(|f| {
-            let fid = f.ident.as_ref().unwrap();
-            let ty = &f.ty;
-
-            let ref_ty = if is_std_string(ty) {
-                quote_spanned!(fid.span()=> ::std::borrow::Cow<'a, str>)
-            } else {
-                quote_spanned!(fid.span()=> &'a #ty)
-            };
+        .map(|p| rename_lt(ctx, &p.to_token_stream(), "static"))
+        .collect();
+    let mut impl_preds: Vec<TokenStream2> = Vec::new();
+    let mut owned_params: Vec<Ident> = Vec::new();
+    let mut seen = HashSet::new();
+    for id in &ctx.type_params {
+        def_preds.push(quote!(#id: 'static));
+        /* `IntoOwned::Owned` is `Clone + Debug`, which the derives bound on `T` */
+        impl_preds.push(quote!(#id: ::std::clone::Clone + ::std::fmt::Debug + 'static));
+    }
+    let shapes = mirror.shapes();
+    for ty in shapes.iter().flat_map(|shape| shape.types()) {
+        let parsed: Type = syn::parse2(ty.clone())?;
+        /* one bound per payload type: two would again compete */
+        if ctx.is_generic(&parsed) && seen.insert(ty.to_string()) {
+            let st = rename_lt(ctx, ty, "static");
+            let any = rename_lt(ctx, ty, "__any");
+            let o = format_ident!("__O{}", owned_params.len());
+            def_preds.push(quote!(#st: ::differs::IntoOwned));
+            impl_preds.push(quote!(for<'__any> #any: ::differs::IntoOwned<Owned = #o>));
+            owned_params.push(o);
+        }
+    }
+    let owned_ty = |ty: &TokenStream2| owned_ty(ctx, ty);
+    let serde = serde_attrs(ctx, attrs, shapes.iter().copied(), true)?;
+    let skip = serde_skip(attrs);
 
-            let init = if is_std_string(ty) {
-                quote_spanned!(fid.span()=> ::std::borrow::Cow::Borrowed(src.#fid.as_str()))
-            } else {
-                quote_spanned!(fid.span()=> &src.#fid)
-            };
+    /* a mirror need not mention every parameter outside a projection */
+    let generic = !generics.params.is_empty();
+    let phantom_ty = quote!(::std::marker::PhantomData<fn() -> #input #ty_generics>);
+
+    /* (owned declaration, borrowed pattern, owned constructor) of a body */
+    let body = |path: TokenStream2, owned_path: TokenStream2, shape: &Shape, phantom: bool| {
+        match shape {
+            Shape::Named(fields) => {
+                let names: Vec<_> = fields.iter().map(|(name, _)| name).collect();
+                let tys = fields.iter().map(|(_, ty)| owned_ty(ty));
+                let (decl_phantom, ctor_phantom) = if phantom {
+                    (
+                        quote!(#[doc(hidden)] #skip __phantom: #phantom_ty,),
+                        quote!(__phantom: ::std::marker::PhantomData,),
+                    )
+                } else {
+                    (quote!(), quote!())
+                };
+                (
+                    quote!({ #( #names: #tys, )* #decl_phantom }),
+                    quote!(#path { #( #names, )* .. }),
+                    quote!(#owned_path { #( #names: ::differs::IntoOwned::into_owned(#names), )* #ctor_phantom }),
+                )
+            }
+            Shape::Unnamed(tys) => {
+                let binds: Vec<_> = (0..tys.len()).map(|i| format_ident!("__{}", i)).collect();
+                let tys = tys.iter().map(&owned_ty);
+                let (decl_phantom, ctor_phantom) = if phantom {
+                    (
+                        quote!(#skip #phantom_ty,),
+                        quote!(::std::marker::PhantomData,),
+                    )
+                } else {
+                    (quote!(), quote!())
+                };
+                (
+                    quote!(( #( #tys, )* #decl_phantom )),
+                    quote!(#path( #( #binds, )* .. )),
+                    quote!(#owned_path( #( ::differs::IntoOwned::into_owned(#binds), )* #ctor_phantom )),
+                )
+            }
+            Shape::Unit => (quote!(), path.clone(), owned_path),
+        }
+    };
 
+    let where_def = quote!(where #( #def_preds, )*);
+    let (decl, arms) = match &mirror.body {
+        Body::Struct(shape) => {
+            let (decl, pat, ctor) = body(quote!(#ident), quote!(#owned_ident), shape, generic);
+            let decl = match shape {
+                Shape::Named(_) => quote!(pub struct #owned_ident #ty_generics #where_def #decl),
+                _ => quote!(pub struct #owned_ident #ty_generics #decl #where_def;),
+            };
+            (decl, vec![quote!(#pat => #ctor)])
+        }
+        Body::Enum(variants) => {
+            let mut decls = Vec::new();
+            let mut arms = Vec::new();
+            for (name, shape) in variants {
+                let (decl, pat, ctor) = body(
+                    quote!(#ident::#name),
+                    quote!(#owned_ident::#name),
+                    shape,
+                    false,
+                );
+                decls.push(quote!(#name #decl));
+                arms.push(quote!(#pat => #ctor));
+            }
+            if generic {
+                decls.push(
+                    quote!(#[doc(hidden)] #skip __phantom(::std::convert::Infallible, #phantom_ty)),
+                );
+            }
+            if mirror.phantom {
+                arms.push(quote!(#ident::__phantom(never, _) => match never {}));
+            }
             (
-                quote_spanned!(fid.span()=> #fid : #ref_ty),
-                quote_spanned!(fid.span()=> #fid : #init),
+                quote!(pub enum #owned_ident #ty_generics #where_def { #( #decls, )* }),
+                arms,
             )
-        })
-        .unzip();
+        }
+    };
 
-    let snapshot_def = quote! {
+    let change_generics = ctx.change_generics(generics, attrs);
+    let (_, change_ty, change_where) = change_generics.split_for_impl();
+    let change_preds = change_where.into_iter().flat_map(|w| &w.predicates);
+    let mut impl_generics = change_generics.clone();
+    impl_generics.params.extend(
+        owned_params
+            .iter()
+            .map(|o| -> GenericParam { parse_quote!(#o) }),
+    );
+    let (change_impl, _, _) = impl_generics.split_for_impl();
+    Ok(quote! {
         #[derive(Debug, Clone)]
-        #[allow(non_camel_case_types, dead_code)]
-        pub struct #snapshot_ident<'a>{ #(#snap_fields,)* }
+        #serde
+        #[allow(non_camel_case_types, private_interfaces, private_bounds)]
+        #decl
 
-        impl<'a> From<&'a #ident #ty_generics> for #snapshot_ident<'a>{
-            fn from(src:&'a #ident #ty_generics)->Self{
-                Self{ #(#snap_init,)* }
+        impl #change_impl ::differs::IntoOwned for #ident #change_ty
+        where #( #change_preds, )* #( #impl_preds, )*
+        {
+            type Owned = #owned_ident #ty_generics;
+
+            fn into_owned(self) -> Self::Owned {
+                match self { #( #arms, )* }
             }
         }
+    })
+}
+
+/* ------------------------------------------------------------------------- */
+/* Patches (`#[differs(patch)]`)                                             */
+/* ------------------------------------------------------------------------- */
+
+/// `patch` needs concrete owned payloads to name `ApplyChanges::Patch`.
+fn check_patch(ctx: &Ctx, ident: &Ident, attrs: &ContainerAttrs) -> syn::Result<()> {
+    if attrs.patch && !ctx.type_params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            ident,
+            "`patch` is not supported on generic types",
+        ));
+    }
+    Ok(())
+}
+
+/// Applies the changes of one field, picked out of `source` by the owned
+/// `variant`, to `target` (`&mut` the field).
+fn apply_field(
+    source: TokenStream2,
+    variant: TokenStream2,
+    diff: &FieldDiff,
+    span: Span,
+    segment: String,
+    target: TokenStream2,
+) -> syn::Result<TokenStream2> {
+    let Some(apply) = &diff.apply else {
+        return Err(syn::Error::new(
+            span,
+            "`patch` does not support `with_module` fields",
+        ));
     };
+    let pick = if diff.pair {
+        quote!(#variant(o, n) => Some((o, n)))
+    } else {
+        quote!(#variant(c) => Some(c))
+    };
+    Ok(quote! {
+        ::differs::__private::apply_part(
+            &#source,
+            |c| match c { #pick, _ => None },
+            #segment,
+            |changes| { let target = #target; #apply },
+        )?;
+    })
+}
+
+/// Field value from its owned snapshot: re-wraps the pointers the snapshot
+/// sees through.
+fn from_snapshot(ty: &Type, v: TokenStream2) -> TokenStream2 {
+    match pointee(ty) {
+        Some((Pointer::Cow, inner)) => {
+            let v = from_snapshot(inner, v);
+            quote!(::std::borrow::Cow::Owned(#v))
+        }
+        Some((_, inner)) => {
+            let v = from_snapshot(inner, v);
+            quote!(::std::convert::From::from(#v))
+        }
+        None => v,
+    }
+}
+
+/// `impl ApplyChanges` with the owned change enum as `Patch`; `body` applies
+/// `patch: Vec<&Self::Patch>` to `self`.
+fn patch_impl(
+    ident: &Ident,
+    generics: &Generics,
+    enum_ident: &Ident,
+    body: TokenStream2,
+) -> TokenStream2 {
+    let owned_ident = format_ident!("{}Owned", enum_ident);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    quote! {
+        impl #impl_generics ::differs::ApplyChanges for #ident #ty_generics #where_clause {
+            type Patch = #owned_ident #ty_generics;
+
+            /* field-less types never look at the patch */
+            #[allow(unreachable_patterns, unused_variables)]
+            fn apply_changes<'__p, __I>(&mut self, patch: __I)
+                -> ::std::result::Result<(), ::differs::PatchError>
+            where __I: ::std::iter::IntoIterator<Item = &'__p Self::Patch> {
+                let patch: Vec<&Self::Patch> = patch.into_iter().collect();
+                #body
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Tuple structs mirror the `Fields` derive and report `item0`, `item1`, …
+/// variants; unit structs get a trivial impl that never reports a change.
+fn derive_struct(
+    ident: &Ident,
+    generics: &Generics,
+    attrs: &ContainerAttrs,
+    ds: DataStruct,
+) -> syn::Result<TokenStream2> {
+    /* (member accessor, `Change` variant ident, field) in declaration order */
+    let members: Vec<(TokenStream2, Ident, &syn::Field)> = ds
+        .fields
+        .iter()
+        .enumerate()
+        .map(|(idx, f)| match &f.ident {
+            Some(fid) => (fid.to_token_stream(), fid.clone(), f),
+            None => {
+                let index = syn::Index::from(idx);
+                let span = f.ty.span();
+                (
+                    quote_spanned!(span=> #index),
+                    format_ident!("item{idx}", span = span),
+                    f,
+                )
+            }
+        })
+        .collect();
+
+    /* common idents */
+    let enum_ident = format_ident!("{ident}Change");
+    let snapshot_ident = format_ident!("{ident}Snapshot");
+    let (_, ty_generics, _) = generics.split_for_impl();
+    let mut ctx = Ctx::new(generics, attrs);
+    let lt = ctx.lt.clone();
+    check_patch(&ctx, ident, attrs)?;
+    let owned_enum = format_ident!("{enum_ident}Owned");
+
+    /* ------------------------------------------------------------------ */
+    /* Borrow-snapshot struct                                             */
+    /* ------------------------------------------------------------------ */
+
+    let (snap_tys, snap_inits): (Vec<_>, Vec<_>) = members
+        .iter()
+        .map(|(member, name, f)| snapshot_field(&ctx, &f.ty, name.span(), quote!(&src.#member)))
+        .unzip();
 
     /* ------------------------------------------------------------------ */
     /* Build `Change` enum + diff logic                                   */
     /* ------------------------------------------------------------------ */
 
     let mut enum_variants = Vec::new();
+    let mut variant_shapes = Vec::new();
     let mut diff_arms = Vec::new();
+    let mut apply_parts = Vec::new();
+    let mut json_parts = Vec::new();
+    let mut path_arms = Vec::new();
+    let mut path_nested = Vec::new();
 
-    /* whole-object snapshot */
-    enum_variants.push(quote! { self_(#snapshot_ident<#lt>) });
-    diff_arms.push(quote! {
-        if old!=new { out.push(#enum_ident::self_(#snapshot_ident::from(new))); }
-    });
+    let field_attrs = members
+        .iter()
+        .map(|(.., f)| FieldAttrs::parse(&f.attrs))
+        .collect::<syn::Result<Vec<_>>>()?;
 
     /* per-field */
-    for f in &fields.named {
-        let fid = f.ident.as_ref().unwrap();
-        let ty = &f.ty;
-        let span = fid.span();
-
-        if has_skip_attr(&f.attrs) {
+    for (idx, ((member, fid, f), field_attrs)) in members.iter().zip(&field_attrs).enumerate() {
+        if field_attrs.skip {
             continue;
         }
 
-        /* container fields */
-        if let Some(kind) = container_kind(ty) {
-            match kind {
-                /* Vec<T> */
-                Container::Vec(elem_ty) => {
-                    let ch_ty = quote_spanned!(span=> ::differs::Changed<#lt,#elem_ty>);
-                    enum_variants.push(quote_spanned!(span=> #fid(#ch_ty)));
+        let diff = field_diff(
+            &mut ctx,
+            &f.ty,
+            field_attrs,
+            fid.span(),
+            quote!(&old.#member),
+            quote!(&new.#member),
+            &|c| quote!(#enum_ident::#fid(#c)),
+        )?;
+        if attrs.patch {
+            apply_parts.push(apply_field(
+                quote!(patch),
+                quote!(#owned_enum::#fid),
+                &diff,
+                fid.span(),
+                member.to_string(),
+                quote!(&mut self.#member),
+            )?);
+        }
+        if attrs.serde {
+            json_parts.push(json_field(
+                quote!(Self::#fid),
+                diff.pair,
+                json_segment(&ds.fields, idx),
+            ));
+        }
+        let nested = !diff.pair && field_attrs.with_module.is_none();
+        if nested {
+            path_nested.push(diff.ty.clone());
+        }
+        path_arms.push(path_arm(quote!(Self::#fid), fid.to_string(), nested));
+        let payload = diff.payload();
+        enum_variants.push(quote_spanned!(fid.span()=> #fid(#(#payload),*)));
+        variant_shapes.push((
+            fid.clone(),
+            Shape::Unnamed(payload.into_iter().cloned().collect()),
+        ));
+        diff_arms.push(diff.arm);
+    }
 
-                    diff_arms.push(quote_spanned!(span=>{
-                        use std::collections::{HashMap, HashSet};
+    let change_generics = ctx.change_generics(generics, attrs);
+    let (change_impl, change_ty, change_where) = change_generics.split_for_impl();
+    let impl_generics = ctx.impl_generics(generics, attrs);
+    let (impl_generics, _, where_clause) = impl_generics.split_for_impl();
 
-                        let old_v = &old.#fid;
-                        let new_v = &new.#fid;
+    let (self_variant, collect) = self_change(
+        attrs,
+        &enum_ident,
+        &snapshot_ident,
+        &change_ty,
+        quote!(#(#diff_arms)*),
+    );
+    /* without `self_`, no variant need borrow or use every parameter */
+    let skip = serde_skip(attrs);
+    let phantom = attrs.no_self.then(|| {
+        quote! {
+            #[doc(hidden)]
+            #skip
+            __phantom(
+                ::std::convert::Infallible,
+                ::std::marker::PhantomData<&#lt #ident #ty_generics>,
+            ),
+        }
+    });
 
-                        /* map value -> queue of old indices  */
-                        let mut idx_map: HashMap<&#elem_ty, Vec<usize>> = HashMap::new();
-                        for (i,v) in old_v.iter().enumerate() {
-                            idx_map.entry(v).or_default().push(i);
-                        }
+    let (snap_body, snap_ctor) = match &ds.fields {
+        Fields::Named(_) => {
+            let names = members.iter().map(|(member, ..)| member);
+            let names2 = names.clone();
+            (
+                quote!(#change_where { #( #names: #snap_tys, )* }),
+                quote!(Self{ #( #names2: #snap_inits, )* }),
+            )
+        }
+        Fields::Unnamed(_) => (
+            quote!(( #( #snap_tys, )* ) #change_where;),
+            quote!(Self( #( #snap_inits, )* )),
+        ),
+        Fields::Unit => (
+            quote!((::std::marker::PhantomData<&#lt ()>) #change_where;),
+            quote!(Self(::std::marker::PhantomData)),
+        ),
+    };
 
-                        /* which old indices were re-used (= kept/moved) */
-                        let mut reused_old : HashSet<usize> = HashSet::new();
-
-                        /* pass 1 – walk the NEW vector and classify */
-                        for (new_idx, val) in new_v.iter().enumerate() {
-                            let q = idx_map.get_mut(val);
-
-                            match q.and_then(|vec| vec.pop()) {
-                                /* identical element existed before */
-                                Some(old_idx) => {
-                                    reused_old.insert(old_idx);
-
-                                    if old_idx != new_idx {
-                                        out.push(#enum_ident::#fid(
-                                            ::differs::Changed::Moved(val, old_idx, new_idx)
-                                        ));
-                                    }
-                                    /* same index -> no change */
-                                }
-                                /* entirely new value */
-                                None => {
-                                    out.push(#enum_ident::#fid(
-                                        ::differs::Changed::AddedAt(new_idx, val, 0)
-                                    ));
-                                }
-                            }
-                        }
+    /* payload types, for `#[differs(serde)]` bounds and owned mirrors */
+    let snap_shape = match &ds.fields {
+        Fields::Named(_) => Shape::Named(
+            members
+                .iter()
+                .zip(&snap_tys)
+                .map(|((_, fid, _), ty)| (fid.clone(), ty.clone()))
+                .collect(),
+        ),
+        _ => Shape::Unnamed(snap_tys.clone()),
+    };
+    if !attrs.no_self {
+        variant_shapes.insert(
+            0,
+            (
+                format_ident!("self_"),
+                Shape::Unnamed(vec![quote!(#snapshot_ident #change_ty)]),
+            ),
+        );
+    }
+    let snap_serde = serde_attrs(&ctx, attrs, [&snap_shape], false)?;
+    let change_serde = serde_attrs(&ctx, attrs, variant_shapes.iter().map(|(_, s)| s), false)?;
 
-                        /* pass 2 – any old indices NOT re-used are removals */
-                        for (old_idx, val) in old_v.iter().enumerate() {
-                            if !reused_old.contains(&old_idx) {
-                                out.push(#enum_ident::#fid(
-                                    ::differs::Changed::RemovedAt(old_idx, val, 0)
-                                ));
-                            }
-                        }
-                    }));
-                }
+    /* `#[differs(serde)]`: JSON Patch from the field changes; `self_` only
+     * restates them */
+    let json_patch = if attrs.serde {
+        let fields = variant_shapes
+            .iter()
+            .filter(|(name, _)| name != "self_")
+            .map(|(_, shape)| shape);
+        json_patch_impl(
+            &ctx,
+            &change_generics,
+            &enum_ident,
+            json_bounds(fields),
+            quote!(#(#json_parts)*),
+        )?
+    } else {
+        quote!()
+    };
+    let path = path_impl(&ctx, &change_generics, &enum_ident, path_nested, path_arms)?;
+    let accessors = changed_accessors(&change_generics, &enum_ident, &variant_shapes);
 
-                /* HashSet<T> */
-                Container::Set(elem_ty) => {
-                    let ch_ty = quote_spanned!(span=> ::differs::Changed<#lt,#elem_ty>);
-                    enum_variants.push(quote_spanned!(span=> #fid(#ch_ty)));
+    let snapshot_def = quote! {
+        #[derive(Clone)]
+        #snap_serde
+        #[allow(non_camel_case_types, dead_code, private_bounds)]
+        pub struct #snapshot_ident #change_impl #snap_body
 
-                    diff_arms.push(quote_spanned!(span=>{
-                        for v in old.#fid.difference(&new.#fid) {
-                            out.push(#enum_ident::#fid(::differs::Changed::Removed(v)));
-                        }
-                        for v in new.#fid.difference(&old.#fid) {
-                            out.push(#enum_ident::#fid(::differs::Changed::Added(v)));
-                        }
-                    }));
-                }
+        impl #change_impl From<&#lt #ident #ty_generics> for #snapshot_ident #change_ty #change_where {
+            fn from(src:&#lt #ident #ty_generics)->Self{
+                #snap_ctor
+            }
+        }
+    };
 
-                /* HashMap<K,V> */
-                Container::Map(k, v) => {
-                    let ch_ty = quote_spanned!(span=> ::differs::MapChanged<#lt,#k,#v>);
-                    enum_variants.push(quote_spanned!(span=> #fid(#ch_ty)));
+    let snapshot = Mirror {
+        ident: snapshot_ident.clone(),
+        body: Body::Struct(snap_shape),
+        phantom: false,
+    };
+    let change = Mirror {
+        ident: enum_ident.clone(),
+        body: Body::Enum(variant_shapes),
+        phantom: attrs.no_self,
+    };
+    let debug = [
+        debug_impl(&ctx, &change_generics, &snapshot)?,
+        debug_impl(&ctx, &change_generics, &change)?,
+    ];
 
-                    diff_arms.push(quote_spanned!(span=>{
-                        /* removals + modifications */
-                        for (k,ov) in &old.#fid {
-                            match new.#fid.get(k) {
-                                None => out.push(#enum_ident::#fid(::differs::MapChanged::RemovedEntry(k,ov))),
-                                Some(nv) if nv!=ov =>
-                                    out.push(#enum_ident::#fid(::differs::MapChanged::ChangedEntry(k))),
-                                _ => {}
-                            }
-                        }
-                        /* pure additions */
-                        for (k,nv) in &new.#fid {
-                            if !old.#fid.contains_key(k) {
-                                out.push(#enum_ident::#fid(::differs::MapChanged::AddedEntry(k,nv)));
-                            }
-                        }
-                    }));
-                }
+    /* `#[differs(owned)]` */
+    let owned = if attrs.owned {
+        let snapshot = owned_mirror(&ctx, ident, generics, attrs, &snapshot)?;
+        let change = owned_mirror(&ctx, ident, generics, attrs, &change)?;
+        quote!(#snapshot #change)
+    } else {
+        quote!()
+    };
+
+    /* `#[differs(patch)]`: `self_` only restates the fields */
+    let patch = if attrs.patch {
+        patch_impl(ident, generics, &enum_ident, quote!(#(#apply_parts)*))
+    } else {
+        quote!()
+    };
+
+    /* ------------------------------------------------------------------ */
+    /* Emit                                                               */
+    /* ------------------------------------------------------------------ */
+
+    Ok(quote_spanned!(ident.span()=>
+        #snapshot_def
+
+        #change_serde
+        #[allow(non_camel_case_types, private_interfaces, private_bounds)]
+        pub enum #enum_ident #change_impl #change_where {
+            #self_variant
+            #( #enum_variants, )*
+            #phantom
+        }
+
+        impl #impl_generics ::differs::HasChanges for #ident #ty_generics #where_clause {
+            type Change<#lt> = #enum_ident #change_ty where Self:#lt;
+            /* field-less structs never look at either side */
+            #[allow(unused_variables)]
+            fn collect_changes<#lt>(old:&#lt Self,new:&#lt Self,out:&mut Vec<Self::Change<#lt>>)
+            where Self:#lt {
+                #collect
             }
-            continue;
         }
 
-        /* nested struct / enum */
-        let treat_as_scalar = is_std_string(ty) || is_primitive(ty);
-        if !treat_as_scalar {
-            if let Type::Path(tp) = ty {
-                let nested_enum = tp
-                    .path
-                    .segments
-                    .last()
-                    .map(|PathSegment { ident, .. }| format_ident!("{ident}Change"))
-                    .unwrap();
-                enum_variants.push(quote_spanned!(span=> #fid(#nested_enum<#lt>)));
-                diff_arms.push(quote_spanned!(span=>{
-                    let mut _subs = Vec::new();
-                    <#ty as ::differs::HasChanges>::collect_changes(&old.#fid,&new.#fid,&mut _subs);
-                    out.extend(_subs.into_iter().map(#enum_ident::#fid));
-                }));
+        #accessors
+        #(#debug)*
+        #owned
+        #patch
+        #json_patch
+        #path
+    ))
+}
+
+/* ------------------------------------------------------------------------- */
+/* Enum handling                                                             */
+/* ------------------------------------------------------------------------- */
+
+/// One field of an enum variant, bound as `old` / `new` in the diff match.
+struct Member<'f> {
+    old: Ident,
+    new: Ident,
+    name: Ident,
+    field: &'f syn::Field,
+    attrs: FieldAttrs,
+}
+
+/// `<Enum>Change` gets `self_`, `VariantChanged { old, new }` (discriminant
+/// differs) and one variant per data-carrying enum variant, wrapping a
+/// `<Enum><Variant>Change` enum with the per-field changes.
+fn derive_enum(
+    ident: &Ident,
+    generics: &Generics,
+    attrs: &ContainerAttrs,
+    de: DataEnum,
+) -> syn::Result<TokenStream2> {
+    let enum_ident = format_ident!("{ident}Change");
+    let snapshot_ident = format_ident!("{ident}Snapshot");
+    let (_, ty_generics, _) = generics.split_for_impl();
+    let mut ctx = Ctx::new(generics, attrs);
+    let lt = ctx.lt.clone();
+    check_patch(&ctx, ident, attrs)?;
+    let owned_enum = format_ident!("{enum_ident}Owned");
+    let snap_owned = format_ident!("{snapshot_ident}Owned");
+
+    let mut snap_variants = Vec::new();
+    let mut snap_arms = Vec::new();
+    let mut enum_variants = Vec::new();
+    let mut variant_enums = Vec::new();
+    let mut diff_arms = Vec::new();
+    /* payload types of the snapshot / per-variant change variants */
+    let mut snap_shapes = Vec::new();
+    let mut var_shapes = Vec::new();
+    /* `#[differs(patch)]`: variant checks and rebuilds for `VariantChanged`,
+     * and the per-variant field changes */
+    let mut patch_checks = Vec::new();
+    let mut patch_rebuilds = Vec::new();
+    let mut patch_variants = Vec::new();
+    /* `#[differs(serde)]`: per-variant field writers, by `<Enum><Variant>Change` */
+    let mut json_variants = Vec::new();
+    /* per-variant path arms and the payloads they descend into */
+    let mut path_variants = Vec::new();
+    for variant in &de.variants {
+        let var_ident = &variant.ident;
+        let var_span = var_ident.span();
+        let var_enum = format_ident!("{ident}{}Change", var_ident.unraw(), span = var_span);
+
+        let members = variant
+            .fields
+            .iter()
+            .enumerate()
+            .map(|(idx, f)| {
+                let (old, new, name) = match &f.ident {
+                    Some(fid) => (
+                        format_ident!("__old_{}", fid.unraw()),
+                        format_ident!("__new_{}", fid.unraw()),
+                        fid.clone(),
+                    ),
+                    None => (
+                        format_ident!("__old_{idx}"),
+                        format_ident!("__new_{idx}"),
+                        format_ident!("item{idx}", span = var_span),
+                    ),
+                };
+                Ok(Member {
+                    old,
+                    new,
+                    name,
+                    field: f,
+                    attrs: FieldAttrs::parse(&f.attrs)?,
+                })
+            })
+            .collect::<syn::Result<Vec<_>>>()?;
+
+        /* variant pattern / constructor / declaration over per-member tokens */
+        let shape = |path: TokenStream2, parts: Vec<TokenStream2>| match &variant.fields {
+            Fields::Named(_) => {
+                let names = members.iter().map(|m| &m.name);
+                quote!(#path #var_ident { #( #names: #parts ),* })
+            }
+            Fields::Unnamed(_) => quote!(#path #var_ident( #( #parts ),* )),
+            Fields::Unit => quote!(#path #var_ident),
+        };
+
+        /* snapshot mirror */
+        let (snap_tys, snap_inits): (Vec<_>, Vec<_>) = members
+            .iter()
+            .map(|m| {
+                let b = &m.old;
+                snapshot_field(&ctx, &m.field.ty, var_span, quote!(#b))
+            })
+            .unzip();
+        let src_pat = shape(
+            quote!(#ident::),
+            members.iter().map(|m| m.old.to_token_stream()).collect(),
+        );
+        let snap_ctor = shape(quote!(Self::), snap_inits);
+        snap_shapes.push((
+            var_ident.clone(),
+            match &variant.fields {
+                Fields::Named(_) => Shape::Named(
+                    members
+                        .iter()
+                        .zip(&snap_tys)
+                        .map(|(m, ty)| (m.name.clone(), ty.clone()))
+                        .collect(),
+                ),
+                Fields::Unnamed(_) => Shape::Unnamed(snap_tys.clone()),
+                Fields::Unit => Shape::Unit,
+            },
+        ));
+        snap_variants.push(shape(quote!(), snap_tys));
+        snap_arms.push(quote!(#src_pat => #snap_ctor));
+
+        /* per-field diffs when both sides hold this variant */
+        let mut var_variants = Vec::new();
+        let mut var_owned = Vec::new();
+        let mut var_arms = Vec::new();
+        let var_owned_enum = format_ident!("{var_enum}Owned");
+        let keys: Vec<TokenStream2> = members
+            .iter()
+            .enumerate()
+            .map(|(idx, m)| match &m.field.ident {
+                Some(fid) => fid.to_token_stream(),
+                None => syn::Index::from(idx).to_token_stream(),
+            })
+            .collect();
+        let mut apply_binds = Vec::new();
+        let mut apply_parts = Vec::new();
+        let mut json_parts = Vec::new();
+        let mut path_arms = Vec::new();
+        let mut path_nested = Vec::new();
+        for (idx, (m, key)) in members.iter().zip(&keys).enumerate() {
+            if m.attrs.skip {
                 continue;
             }
+            let (old_b, new_b, fid) = (&m.old, &m.new, &m.name);
+            let diff = field_diff(
+                &mut ctx,
+                &m.field.ty,
+                &m.attrs,
+                fid.span(),
+                quote!(#old_b),
+                quote!(#new_b),
+                &|c| quote!(#enum_ident::#var_ident(#var_enum::#fid(#c))),
+            )?;
+            if attrs.patch {
+                let bind = format_ident!("__target_{idx}");
+                apply_parts.push(apply_field(
+                    quote!(changes),
+                    quote!(#var_owned_enum::#fid),
+                    &diff,
+                    fid.span(),
+                    key.to_string(),
+                    quote!(#bind),
+                )?);
+                apply_binds.push(quote!(#key: #bind));
+            }
+            if attrs.serde {
+                json_parts.push(json_field(
+                    quote!(Self::#fid),
+                    diff.pair,
+                    json_segment(&variant.fields, idx),
+                ));
+            }
+            let nested = !diff.pair && m.attrs.with_module.is_none();
+            if nested {
+                path_nested.push(diff.ty.clone());
+            }
+            path_arms.push(path_arm(quote!(Self::#fid), fid.to_string(), nested));
+            let payload = diff.payload();
+            var_variants.push(quote_spanned!(fid.span()=> #fid(#(#payload),*)));
+            var_owned.push((
+                fid.clone(),
+                Shape::Unnamed(payload.into_iter().cloned().collect()),
+            ));
+            var_arms.push(diff.arm);
         }
 
-        /* scalar field */
-        let scalar_ty = if is_std_string(ty) {
-            quote_spanned!(span=> ::std::borrow::Cow<#lt,str>)
-        } else {
-            quote_spanned!(span=> &#lt #ty)
+        let bind = |old_side: bool| -> Vec<TokenStream2> {
+            members
+                .iter()
+                .map(|m| {
+                    if m.attrs.skip {
+                        quote!(_)
+                    } else if old_side {
+                        m.old.to_token_stream()
+                    } else {
+                        m.new.to_token_stream()
+                    }
+                })
+                .collect()
         };
-        enum_variants.push(quote_spanned!(span=> #fid(#scalar_ty)));
+        let old_pat = shape(quote!(#ident::), bind(true));
+        let new_pat = shape(quote!(#ident::), bind(false));
+        diff_arms.push(quote!((#old_pat, #new_pat) => { #(#var_arms)* }));
 
-        let new_val = if is_std_string(ty) {
-            quote_spanned!(span=> ::std::borrow::Cow::Borrowed(new.#fid.as_str()))
-        } else {
-            quote_spanned!(span=> &new.#fid)
-        };
-        diff_arms.push(quote_spanned!(span=>{
-            if old.#fid != new.#fid {
-                out.push(#enum_ident::#fid(#new_val));
+        if attrs.patch {
+            let binds: Vec<_> = (0..members.len())
+                .map(|idx| format_ident!("__snap_{idx}"))
+                .collect();
+            let values = members
+                .iter()
+                .zip(&binds)
+                .map(|(m, b)| from_snapshot(&m.field.ty, quote!(#b)));
+            patch_checks.push(quote!((Self::#var_ident { .. }, #snap_owned::#var_ident { .. })));
+            patch_rebuilds.push(quote! {
+                #snap_owned::#var_ident { #( #keys: #binds ),* } => Self::#var_ident { #( #keys: #values ),* }
+            });
+            if !apply_parts.is_empty() {
+                let segment = var_ident.to_string();
+                patch_variants.push(quote! {
+                    ::differs::__private::apply_part(
+                        &patch,
+                        |c| match c { #owned_enum::#var_ident(c) => Some(c), _ => None },
+                        #segment,
+                        |changes| match self {
+                            Self::#var_ident { #( #apply_binds, )* .. } => {
+                                #( #apply_parts )*
+                                Ok(())
+                            }
+                            _ => Err(::differs::PatchError::new(::differs::PatchErrorKind::Variant)),
+                        },
+                    )?;
+                });
             }
-        }));
+        }
+
+        if !var_variants.is_empty() {
+            json_variants.push(json_parts);
+            path_variants.push((path_nested, path_arms));
+            var_shapes.push((var_ident.clone(), var_enum.clone(), var_owned));
+            enum_variants.push((var_span, var_ident, var_enum, var_variants));
+        }
     }
 
-    /* ------------------------------------------------------------------ */
-    /* Emit                                                               */
-    /* ------------------------------------------------------------------ */
+    /* field-less enums still need to use the snapshot lifetime */
+    let skip = serde_skip(attrs);
+    if de.variants.iter().all(|v| v.fields.is_empty()) {
+        snap_variants.push(quote! {
+            #[doc(hidden)]
+            #skip
+            __phantom(::std::convert::Infallible, ::std::marker::PhantomData<&#lt ()>)
+        });
+    }
 
-    let expanded = quote_spanned!(ident.span()=>
-        #snapshot_def
+    let change_generics = ctx.change_generics(generics, attrs);
+    let (change_impl, change_ty, change_where) = change_generics.split_for_impl();
+    let impl_generics = ctx.impl_generics(generics, attrs);
+    let (impl_generics, _, where_clause) = impl_generics.split_for_impl();
+
+    let (self_variant, collect) = self_change(
+        attrs,
+        &enum_ident,
+        &snapshot_ident,
+        &change_ty,
+        quote! {
+            #[allow(unreachable_patterns)]
+            match (old, new) {
+                #( #diff_arms )*
+                _ => out.push(#enum_ident::VariantChanged {
+                    old: #snapshot_ident::from(old),
+                    new: #snapshot_ident::from(new),
+                }),
+            }
+        },
+    );
 
-        #[derive(Debug)]
-        #[allow(non_camel_case_types)]
-        pub enum #enum_ident<#lt>{ #( #enum_variants, )* }
+    let variant_changes = enum_variants.iter().map(
+        |(span, var_ident, var_enum, _)| quote_spanned!(*span=> #var_ident(#var_enum #change_ty)),
+    );
+    /* a variant need not use every parameter of the enum */
+    let phantom = (!generics.params.is_empty()).then(|| {
+        quote! {
+            #[doc(hidden)]
+            #skip
+            __phantom(
+                ::std::convert::Infallible,
+                ::std::marker::PhantomData<&#lt #ident #ty_generics>,
+            ),
+        }
+    });
 
-        impl ::differs::HasChanges for #ident #ty_generics #where_clause {
-            type Change<'a> = #enum_ident<'a> where Self:'a;
-            fn collect_changes<'a>(old:&'a Self,new:&'a Self,out:&mut Vec<Self::Change<'a>>)
-            where Self:'a {
-                #(#diff_arms)*
+    /* generated types: snapshot, `<Enum><Variant>Change`s, then `<Enum>Change` */
+    let snap_ty = quote!(#snapshot_ident #change_ty);
+    let mut changes = Vec::new();
+    if !attrs.no_self {
+        changes.push((
+            format_ident!("self_"),
+            Shape::Unnamed(vec![snap_ty.clone()]),
+        ));
+    }
+    changes.push((
+        format_ident!("VariantChanged"),
+        Shape::Named(vec![
+            (format_ident!("old"), snap_ty.clone()),
+            (format_ident!("new"), snap_ty),
+        ]),
+    ));
+    let mut mirrors = vec![Mirror {
+        ident: snapshot_ident.clone(),
+        body: Body::Enum(snap_shapes),
+        phantom: de.variants.iter().all(|v| v.fields.is_empty()),
+    }];
+    for (var_ident, var_enum, var_owned) in var_shapes {
+        changes.push((
+            var_ident,
+            Shape::Unnamed(vec![quote!(#var_enum #change_ty)]),
+        ));
+        mirrors.push(Mirror {
+            ident: var_enum,
+            body: Body::Enum(var_owned),
+            phantom: !generics.params.is_empty(),
+        });
+    }
+    mirrors.push(Mirror {
+        ident: enum_ident.clone(),
+        body: Body::Enum(changes),
+        phantom: false,
+    });
+    let mut serde = mirrors
+        .iter()
+        .map(|m| serde_attrs(&ctx, attrs, m.shapes(), false))
+        .collect::<syn::Result<Vec<_>>>()?;
+    let change_serde = serde.pop();
+    let snap_serde = serde.remove(0);
+    let accessors = mirrors[1..].iter().map(|m| match &m.body {
+        Body::Enum(variants) => changed_accessors(&change_generics, &m.ident, variants),
+        Body::Struct(_) => quote!(),
+    });
+    let accessors = quote!(#(#accessors)*);
+    let debug = mirrors
+        .iter()
+        .map(|m| debug_impl(&ctx, &change_generics, m))
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    for ((span, _, var_enum, var_variants), var_serde) in enum_variants.iter().zip(&serde) {
+        variant_enums.push(quote_spanned!(*span=>
+            #var_serde
+            #[allow(non_camel_case_types, private_interfaces, private_bounds)]
+            pub enum #var_enum #change_impl #change_where { #( #var_variants, )* #phantom }
+        ));
+    }
+
+    /* `#[differs(serde)]`: a `VariantChanged` replaces the value with the
+     * snapshot, which serializes like the enum */
+    let json_patch = if attrs.serde {
+        let serde = quote!(::differs::__private::serde);
+        let mut bounds = vec![(
+            quote!(#snapshot_ident #change_ty),
+            quote!(#serde::Serialize),
+        )];
+        let mut parts = Vec::new();
+        let mut impls = Vec::new();
+        for ((_, var_ident, var_enum, _), (json_parts, mirror)) in enum_variants
+            .iter()
+            .zip(json_variants.iter().zip(&mirrors[1..]))
+        {
+            bounds.push((
+                quote!(#var_enum #change_ty),
+                quote!(::differs::JsonPatchOps),
+            ));
+            let segment = format!("/{}", var_ident.unraw());
+            parts.push(json_field(quote!(Self::#var_ident), false, segment));
+            impls.push(json_patch_impl(
+                &ctx,
+                &change_generics,
+                var_enum,
+                json_bounds(mirror.shapes()),
+                quote!(#(#json_parts)*),
+            )?);
+        }
+        let top = json_patch_impl(
+            &ctx,
+            &change_generics,
+            &enum_ident,
+            bounds,
+            quote! {
+                for c in changes {
+                    if let Self::VariantChanged { new, .. } = c {
+                        ::differs::__private::json_replace(new, at, out)?;
+                    }
+                }
+                #( #parts )*
+            },
+        )?;
+        quote!(#(#impls)* #top)
+    } else {
+        quote!()
+    };
+
+    /* paths: `Variant.field`, like the `Fields` derive */
+    let mut path = Vec::new();
+    let (mut nested, mut arms) = (Vec::new(), Vec::new());
+    for ((_, var_ident, var_enum, _), (var_nested, var_arms)) in
+        enum_variants.iter().zip(path_variants)
+    {
+        path.push(path_impl(
+            &ctx,
+            &change_generics,
+            var_enum,
+            var_nested,
+            var_arms,
+        )?);
+        nested.push(quote!(#var_enum #change_ty));
+        arms.push(path_arm(
+            quote!(Self::#var_ident),
+            var_ident.to_string(),
+            true,
+        ));
+    }
+    path.push(path_impl(
+        &ctx,
+        &change_generics,
+        &enum_ident,
+        nested,
+        arms,
+    )?);
+
+    /* `#[differs(owned)]` */
+    let owned = if attrs.owned {
+        mirrors
+            .iter()
+            .map(|m| owned_mirror(&ctx, ident, generics, attrs, m))
+            .collect::<syn::Result<TokenStream2>>()?
+    } else {
+        quote!()
+    };
+
+    /* `#[differs(patch)]`: a `VariantChanged` rebuilds the value from `new` */
+    let patch = if attrs.patch {
+        let current = if patch_checks.is_empty() {
+            quote!(false)
+        } else {
+            quote!(matches!((&*self, old), #( #patch_checks )|*))
+        };
+        patch_impl(
+            ident,
+            generics,
+            &enum_ident,
+            quote! {
+                for c in &patch {
+                    if let #owned_enum::VariantChanged { old, new } = c {
+                        if !#current {
+                            return Err(::differs::PatchError::new(::differs::PatchErrorKind::Variant));
+                        }
+                        *self = match ::std::clone::Clone::clone(new) { #( #patch_rebuilds, )* };
+                    }
+                }
+                #( #patch_variants )*
+            },
+        )
+    } else {
+        quote!()
+    };
+
+    Ok(quote_spanned!(ident.span()=>
+        #[derive(Clone)]
+        #snap_serde
+        #[allow(non_camel_case_types, dead_code, private_interfaces, private_bounds)]
+        pub enum #snapshot_ident #change_impl #change_where { #( #snap_variants, )* }
+
+        impl #change_impl From<&#lt #ident #ty_generics> for #snapshot_ident #change_ty #change_where {
+            fn from(src:&#lt #ident #ty_generics)->Self{
+                match src { #( #snap_arms, )* }
+            }
+        }
+
+        #(#variant_enums)*
+
+        #change_serde
+        #[allow(non_camel_case_types, private_interfaces, private_bounds)]
+        pub enum #enum_ident #change_impl #change_where {
+            #self_variant
+            VariantChanged { old: #snapshot_ident #change_ty, new: #snapshot_ident #change_ty },
+            #( #variant_changes, )*
+        }
+
+        impl #impl_generics ::differs::HasChanges for #ident #ty_generics #where_clause {
+            type Change<#lt> = #enum_ident #change_ty where Self:#lt;
+            fn collect_changes<#lt>(old:&#lt Self,new:&#lt Self,out:&mut Vec<Self::Change<#lt>>)
+            where Self:#lt {
+                #collect
             }
         }
-    );
 
-    TokenStream::from(expanded)
+        #accessors
+        #(#debug)*
+        #owned
+        #patch
+        #json_patch
+        #(#path)*
+    ))
 }
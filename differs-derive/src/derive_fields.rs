@@ -3,7 +3,8 @@
 use proc_macro::TokenStream;
 use quote::{format_ident, quote, quote_spanned};
 use syn::{
-    Attribute, Data, DataEnum, DataStruct, DeriveInput, Fields, Ident, Type, parse_macro_input,
+    Attribute, Data, DataEnum, DataStruct, DeriveInput, Fields, Ident, Meta, Token, Type,
+    parse_macro_input, punctuated::Punctuated,
 };
 
 /* ------------------------------------------------------------------------- */
@@ -22,6 +23,25 @@ fn has_skip_attr(attrs: &[Attribute]) -> bool {
     })
 }
 
+/// `#[differs(scalar)]`, `#[differs(opaque)]`, `#[differs(eq = ..)]`,
+/// `#[differs(with = ..)]` or `#[differs(with_module = ..)]`: the field is
+/// diffed as a whole, so it has no sub-fields.
+fn has_leaf_attr(attrs: &[Attribute]) -> bool {
+    attrs.iter().any(|attr| {
+        attr.path().is_ident("differs")
+            && attr
+                .parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)
+                .is_ok_and(|metas| {
+                    metas.iter().any(|meta| {
+                        let path = meta.path();
+                        ["scalar", "opaque", "eq", "with", "with_module"]
+                            .iter()
+                            .any(|key| path.is_ident(key))
+                    })
+                })
+    })
+}
+
 fn is_std_string(ty: &Type) -> bool {
     matches!(
         ty,
@@ -54,7 +74,14 @@ fn is_container(ty: &Type) -> bool {
     };
     matches!(
         seg.ident.to_string().as_str(),
-        "Vec" | "HashSet" | "HashMap" | "BTreeSet" | "BTreeMap"
+        "Vec"
+            | "VecDeque"
+            | "HashSet"
+            | "HashMap"
+            | "BTreeSet"
+            | "BTreeMap"
+            | "IndexSet"
+            | "IndexMap"
     )
 }
 
@@ -169,7 +196,7 @@ fn handle_struct(
                     continue;
                 }
 
-                if is_leaf(&field.ty) {
+                if is_leaf(&field.ty) || has_leaf_attr(&field.attrs) {
                     methods.push(quote_spanned! { f_ident.span() =>
                         #[allow(non_snake_case)]
                         pub fn #f_ident(&self) -> ::differs::FieldName {
@@ -202,7 +229,7 @@ fn handle_struct(
                     continue;
                 }
 
-                if is_leaf(&field.ty) {
+                if is_leaf(&field.ty) || has_leaf_attr(&field.attrs) {
                     methods.push(
                         quote_spanned! {  field.ident.as_ref().unwrap_or(struct_ident).span() =>
                             #[allow(non_snake_case)]
@@ -281,7 +308,7 @@ fn handle_enum(
                         continue;
                     }
 
-                    if is_leaf(&field.ty) {
+                    if is_leaf(&field.ty) || has_leaf_attr(&field.attrs) {
                         item_methods.push(quote_spanned! {  field.ident.as_ref().unwrap_or(variant_ident).span() =>
                             #[allow(non_snake_case)]
                             pub fn #item_fn(&self) -> ::differs::FieldName {
@@ -341,7 +368,7 @@ fn handle_enum(
                         continue;
                     }
 
-                    if is_leaf(&field.ty) {
+                    if is_leaf(&field.ty) || has_leaf_attr(&field.attrs) {
                         proxy_methods.push(quote_spanned! { f_ident.span() =>
                             #[allow(non_snake_case)]
                             pub fn #f_ident(&self) -> ::differs::FieldName {
@@ -1,5 +1,6 @@
 use proc_macro::TokenStream;
 
+mod attrs;
 mod derive_diff;
 mod derive_fields;
 
@@ -8,6 +9,16 @@ pub fn diff_fields(input: TokenStream) -> TokenStream {
     derive_fields::derive_fields_impl(input)
 }
 
+/// Implements `HasChanges` with a `<Name>Change` enum of per-field changes.
+///
+/// Fields spelled `Vec`, `VecDeque`, `HashSet`, `BTreeSet`, `HashMap`,
+/// `BTreeMap` or `Option` are diffed inline, so `#[differs(...)]` can pick
+/// their algorithm; a plain `Vec<T>` is matched by hash. Any other type,
+/// including an alias such as `type Ids = Vec<u32>`, is diffed through its
+/// own `HasChanges` impl: for `Vec` that is a Myers edit script. Elements,
+/// values and pointees always go through `HasChanges`, whose `SCALAR` and
+/// `EQ_HASH` decide whether they are replaced or modified in place, and
+/// how equal ones are grouped.
 #[proc_macro_derive(Diff, attributes(differs))]
 pub fn diff_changes(input: TokenStream) -> TokenStream {
     derive_diff::derive_diff_impl(input)
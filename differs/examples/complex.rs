@@ -1,14 +1,11 @@
 use differs::{
     changed, diff_changes,
-    Changed::{Added, AddedAt, Moved, Removed, RemovedAt},
+    Changed::{Added, AddedAt, ModifiedAt, Moved, Removed, RemovedAt},
     Diff, Fields,
     MapChanged::{AddedEntry, ChangedEntry, RemovedEntry},
 };
 use serde::{Deserialize, Serialize};
-use std::{
-    collections::{HashMap, HashSet},
-    hash::{Hash, Hasher},
-};
+use std::collections::{HashMap, HashSet};
 
 #[derive(Diff, Fields, Clone, Debug, Serialize, Deserialize, Hash, PartialEq, Eq)]
 struct Address {
@@ -16,7 +13,7 @@ struct Address {
     city: String,
 }
 
-#[derive(Diff, Fields, Clone, Debug, Serialize, Deserialize)]
+#[derive(Diff, Fields, Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
 pub struct Account {
     id: u32,
     username: String,
@@ -26,21 +23,10 @@ pub struct Account {
     address: Address,
 }
 
-impl PartialEq for Account {
-    fn eq(&self, other: &Self) -> bool {
-        self.id == other.id
-    }
-}
-impl Eq for Account {}
-impl Hash for Account {
-    fn hash<H: Hasher>(&self, s: &mut H) {
-        self.id.hash(s)
-    }
-}
-
 #[derive(Diff, Fields, Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
 pub struct Company {
     name: String,
+    #[differs(key = id)]
     staff: Vec<Account>,
 }
 
@@ -56,6 +42,11 @@ fn dump_company(changes: &[CompanyChange<'_>]) {
             Company.staff(RemovedAt(i, acc, _)) => { println!("Company.staff(RemovedAt({i}, {}))", acc.username); };
 
             Company.staff(Moved(acc, f, t))     => { println!("Company.staff(Moved({}, {f}->{t}))", acc.username); };
+
+            Company.staff(ModifiedAt(i, acc, subs)) => {
+                println!("Company.staff(ModifiedAt({i}, {}))", acc.username);
+                dump_account(subs);
+            };
         );
     }
 }
@@ -83,8 +74,8 @@ fn dump_account(changes: &[AccountChange<'_>]) {
             Account.preferences(RemovedEntry(k, v))     => {
                 println!("Account.preferences(RemovedEntry({k}, {v}))");
             };
-            Account.preferences(ChangedEntry(k))        => {
-                println!("Account.preferences(ChangedEntry({k}))");
+            Account.preferences(ChangedEntry(k, o, n))  => {
+                println!("Account.preferences(ChangedEntry({k}, {o} -> {n}))");
             };
         );
     }
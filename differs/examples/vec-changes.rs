@@ -1,7 +1,7 @@
 
 use differs::{
     changed, diff_changes,
-    Changed::{Added, AddedAt, Moved, Removed, RemovedAt},
+    Changed::{Added, AddedAt, AddedRange, Moved, Removed, RemovedAt, RemovedRange},
     Diff,
 };
 
@@ -10,14 +10,35 @@ struct Bag {
     items: Vec<char>,
 }
 
+/// Same shape, diffed as an edit script: shifts are not reported as moves.
+#[derive(Diff, Clone, Debug, PartialEq)]
+struct Script {
+    #[differs(algorithm = "myers", ranges)]
+    items: Vec<char>,
+}
+
+fn dump_script(label: &str, changes: &[ScriptChange]) {
+    println!("\n=== {label} (myers) ===");
+    for ch in changes {
+        changed!(ch;
+            Script.items(AddedAt(i, v, _))   => { println!("AddedAt(idx={i:<2}, val={v:?})"); };
+            Script.items(RemovedAt(i, v, _)) => { println!("RemovedAt(idx={i:<2}, val={v:?})"); };
+            Script.items(Moved(v,from,to))   => { println!("Moved(val={v:?}, from={from}, to={to})"); };
+            Script.items(AddedRange(i, vs))  => { println!("AddedRange(idx={i:<2}, vals={vs:?})"); };
+            Script.items(RemovedRange(i, vs)) => { println!("RemovedRange(idx={i:<2}, vals={vs:?})"); };
+        );
+    }
+}
+
 fn dump(label: &str, changes: &[BagChange]) {
     println!("\n=== {label} ===");
     for ch in changes {
         changed!(ch;
             Bag.items(Added(v))             => { println!("Added({v:?})"); };
             Bag.items(Removed(v))           => { println!("Removed({v:?})"); };
-            Bag.items(AddedAt(i, v, c))     => { println!("AddedAt(idx={i:<2}, val={v:?}, c=#{c})"); };
-            Bag.items(RemovedAt(i, v, c))   => { println!("RemovedAt(idx={i:<2}, val={v:?},c=#{c})"); };
+            // `n`: how many equal values precede this one (tells duplicates apart)
+            Bag.items(AddedAt(i, v, n))     => { println!("AddedAt(idx={i:<2}, val={v:?}, nth={n})"); };
+            Bag.items(RemovedAt(i, v, n))   => { println!("RemovedAt(idx={i:<2}, val={v:?}, nth={n})"); };
             Bag.items(Moved(v,from,to))     => { println!("Moved(val={v:?}, from={from}, to={to})"); };
         );
     }
@@ -41,6 +62,10 @@ fn main() {
         items: vec!['B', 'C'],
     };
     dump("[A, B, C] -> [B, C]", &diff_changes(&old, &new));
+    dump_script(
+        "[A, B, C] -> [B, C]",
+        &diff_changes(&Script { items: old.items }, &Script { items: new.items }),
+    );
 
     // 3. Mixed removal + moves with duplicates
     let old = Bag {
@@ -59,4 +84,13 @@ fn main() {
         items: vec!['X', 'A', 'Y', 'A'],
     };
     dump("[X,Y] -> [X,A, Y,A]", &diff_changes(&old, &new));
+
+    // 5. Bulk insert + bulk removal collapse into ranges
+    let old = Script {
+        items: vec!['A', 'B', 'C', 'D', 'E'],
+    };
+    let new = Script {
+        items: vec!['A', 'X', 'Y', 'Z', 'B', 'E'],
+    };
+    dump_script("[A,B,C,D,E] -> [A,X,Y,Z,B,E]", &diff_changes(&old, &new));
 }
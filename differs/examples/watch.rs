@@ -1,35 +1,49 @@
-use differs::{ChangeEventBus, Fields, HasFields as _};
+use differs::{ChangeEventBus, Diff, Fields, HasFields as _};
 use serde::{Deserialize, Serialize};
 
-#[derive(Fields, Serialize, Deserialize, Debug, Clone)]
+#[derive(Diff, Fields, Serialize, Deserialize, Debug, Clone)]
 struct Foo {
     a: i64,
     bar: Bar,
 }
 
-#[derive(Fields, Serialize, Deserialize, Debug, Clone)]
+#[derive(Diff, Fields, Serialize, Deserialize, Debug, Clone)]
 struct Bar {
     c: String,
     b: Baz,
 }
-#[derive(Fields, Serialize, Deserialize, Debug, Clone)]
+#[derive(Diff, Fields, Serialize, Deserialize, Debug, Clone)]
 struct Baz {
     d: String,
 }
 
 fn main() {
     let mut bus = ChangeEventBus::new();
-    
+
     // Subscribe to a simple scalar
     let rx_a = bus.subscribe(Foo::fields().a());
 
     // Subscribe to a nested field
     let rx_c = bus.subscribe(Foo::fields().bar().c());
 
-    // Pretend our file-watcher detected changes:
-    bus.publish("a", "42".into());
-    bus.publish("bar.c", "\"updated\"".into());
+    // Untouched, so never published
+    let rx_d = bus.subscribe(Foo::fields().bar().b().d());
+
+    let old = Foo {
+        a: 1,
+        bar: Bar {
+            c: "initial".into(),
+            b: Baz { d: "same".into() },
+        },
+    };
+
+    // Pretend our file-watcher reloaded the config:
+    let mut new = old.clone();
+    new.a = 42;
+    new.bar.c = "updated".into();
+    bus.publish_diff(&old, &new).unwrap();
 
     println!("got a   = {}", rx_a.recv().unwrap());
     println!("got bar.c = {}", rx_c.recv().unwrap());
+    println!("bar.b.d quiet: {}", rx_d.try_recv().is_err());
 }
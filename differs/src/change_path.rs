@@ -0,0 +1,152 @@
+//! [`FieldName`] paths of changes, in the form the `Fields` derive builds
+//! them, extended with container positions: `address.city`,
+//! `staff[3].username`, `preferences["theme"]`.
+
+use std::{borrow::Cow, fmt::Debug};
+
+use crate::{Changed, FieldName, MapChanged, OptionChanged, TupleChanged, Unused};
+
+/// Changes that know the path of the value they changed.
+///
+/// Implemented for the library's change types and by **`#[derive(Diff)]`**
+/// for every `<Name>Change` enum.
+pub trait ChangePath {
+    /// The path of this change within a value found at `at`.
+    fn path_below(&self, at: &FieldName) -> FieldName;
+
+    /// The path of this change from the diffed value; empty for the value
+    /// itself (`self_`, a scalar's new value).
+    fn path(&self) -> FieldName {
+        self.path_below(&FieldName::static_lit(""))
+    }
+
+    /// Pushes the paths below `at` that a moved element left, which
+    /// [`path_below`](Self::path_below) (its new index) does not name.
+    fn vacated_below(&self, at: &FieldName, out: &mut Vec<FieldName>) {
+        let _ = (at, out);
+    }
+}
+
+/// The one path below `at` that every nested change shares beyond `at`
+/// itself, or `at`. A derived element's `self_` sits at `at`, so an element
+/// with one changed field reports that field's path.
+pub(crate) fn nested_path<C: ChangePath>(at: FieldName, subs: &[C]) -> FieldName {
+    let mut paths = subs.iter().map(|c| c.path_below(&at)).filter(|p| *p != at);
+    match paths.next() {
+        Some(first) if paths.all(|p| p == first) => first,
+        _ => at,
+    }
+}
+
+
+/* ------------------------------------------------------------------------- */
+/* Scalars                                                                   */
+/* ------------------------------------------------------------------------- */
+
+impl<T: ?Sized> ChangePath for &T {
+    fn path_below(&self, at: &FieldName) -> FieldName {
+        at.clone()
+    }
+}
+
+impl<B: ToOwned + ?Sized> ChangePath for Cow<'_, B> {
+    fn path_below(&self, at: &FieldName) -> FieldName {
+        at.clone()
+    }
+}
+
+/// Nested change type of a container whose elements do not derive `Diff`.
+impl ChangePath for () {
+    fn path_below(&self, at: &FieldName) -> FieldName {
+        at.clone()
+    }
+}
+
+impl ChangePath for Unused {
+    fn path_below(&self, _: &FieldName) -> FieldName {
+        match *self {}
+    }
+
+    fn vacated_below(&self, _: &FieldName, _: &mut Vec<FieldName>) {
+        match *self {}
+    }
+}
+
+/* ------------------------------------------------------------------------- */
+/* Containers                                                                */
+/* ------------------------------------------------------------------------- */
+
+/// Elements by index (the new one, or the old one of a removal); set
+/// elements have no position and report the set.
+impl<T, C: ChangePath> ChangePath for Changed<'_, T, C> {
+    fn path_below(&self, at: &FieldName) -> FieldName {
+        match self {
+            Changed::Added(_) | Changed::Removed(_) => at.clone(),
+            Changed::AddedAt(idx, ..)
+            | Changed::RemovedAt(idx, ..)
+            | Changed::Moved(_, _, idx)
+            | Changed::AddedRange(idx, _)
+            | Changed::RemovedRange(idx, _) => at.index(*idx),
+            Changed::ModifiedAt(idx, _, subs) => nested_path(at.index(*idx), subs),
+        }
+    }
+
+    fn vacated_below(&self, at: &FieldName, out: &mut Vec<FieldName>) {
+        match self {
+            Changed::Moved(_, from, _) => out.push(at.index(*from)),
+            Changed::ModifiedAt(idx, _, subs) => {
+                let at = at.index(*idx);
+                subs.iter().for_each(|c| c.vacated_below(&at, out));
+            }
+            _ => {}
+        }
+    }
+}
+
+impl<K: Debug, V, C: ChangePath> ChangePath for MapChanged<'_, K, V, C> {
+    fn path_below(&self, at: &FieldName) -> FieldName {
+        match self {
+            MapChanged::AddedEntry(k, _)
+            | MapChanged::RemovedEntry(k, _)
+            | MapChanged::ChangedEntry(k, ..) => at.key(k),
+            MapChanged::ModifiedEntry(k, subs) => nested_path(at.key(k), subs),
+        }
+    }
+
+    fn vacated_below(&self, at: &FieldName, out: &mut Vec<FieldName>) {
+        if let MapChanged::ModifiedEntry(k, subs) = self {
+            let at = at.key(k);
+            subs.iter().for_each(|c| c.vacated_below(&at, out));
+        }
+    }
+}
+
+/// An option is transparent: its inner value sits at its own path.
+impl<T, C: ChangePath> ChangePath for OptionChanged<'_, T, C> {
+    fn path_below(&self, at: &FieldName) -> FieldName {
+        match self {
+            OptionChanged::Set(_) | OptionChanged::Unset(_) => at.clone(),
+            OptionChanged::Inner(c) => c.path_below(at),
+        }
+    }
+
+    fn vacated_below(&self, at: &FieldName, out: &mut Vec<FieldName>) {
+        if let OptionChanged::Inner(c) = self {
+            c.vacated_below(at, out);
+        }
+    }
+}
+
+macro_rules! tuple_change_path {
+    ( $( $p:ident ),* ) => {
+        /// The `Fields` derive reaches a tuple only as a whole, so every
+        /// element change reports the tuple's own path.
+        impl<$( $p ),*> ChangePath for TupleChanged<$( $p ),*> {
+            fn path_below(&self, at: &FieldName) -> FieldName {
+                at.clone()
+            }
+        }
+    };
+}
+
+tuple_change_path!(P0, P1, P2, P3, P4, P5, P6, P7, P8, P9, P10, P11);
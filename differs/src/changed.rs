@@ -1,28 +1,133 @@
-
+/// Uninhabited placeholder for the [`TupleChanged`] positions past a
+/// tuple's arity, and the change type of values that never change
+/// (`PhantomData`).
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum Changed<'a, T: 'a> {
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Unused {}
+
+/// `C` is the element's own change type when it derives `Diff`.
+///
+/// With the `serde` feature, serialized as an object tagged by `"op"`:
+///
+/// | variant        | wire shape                                               |
+/// |----------------|----------------------------------------------------------|
+/// | `Added`        | `{"op": "added", "value": v}`                            |
+/// | `Removed`      | `{"op": "removed", "value": v}`                          |
+/// | `AddedAt`      | `{"op": "added_at", "index": i, "value": v, "nth": n}`   |
+/// | `RemovedAt`    | `{"op": "removed_at", "index": i, "value": v, "nth": n}` |
+/// | `Moved`        | `{"op": "moved", "value": v, "from": i, "to": j}`        |
+/// | `AddedRange`   | `{"op": "added_range", "index": i, "values": [..]}`      |
+/// | `RemovedRange` | `{"op": "removed_range", "index": i, "values": [..]}`    |
+/// | `ModifiedAt`   | `{"op": "modified_at", "index": i, "value": v, "changes": [..]}` |
+///
+/// [`ChangedOwned`](crate::ChangedOwned) shares the shape and deserializes
+/// from it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Changed<'a, T: 'a, C = ()> {
     Added(&'a T),
     Removed(&'a T),
+    /// `(index, value, nth)`: `nth` counts the equal values before `index`
+    /// in the new vector, telling duplicates apart (`0` for the first).
     AddedAt(usize, &'a T, usize),
+    /// `(index, value, nth)`: as `AddedAt`, counted in the old vector.
     RemovedAt(usize, &'a T, usize),
+    /// `(value, old index, new index)`
     Moved(&'a T, usize, usize),
-    /// Element remained at its index but mutated in-place.
-    ModifiedAt(usize, &'a T),
+    /// Contiguous insertion starting at the index (`#[differs(ranges)]`).
+    AddedRange(usize, &'a [T]),
+    /// Contiguous removal starting at the old index (`#[differs(ranges)]`).
+    RemovedRange(usize, &'a [T]),
+    /// Element remained at its index but mutated in-place; carries the new
+    /// element and its nested change list.
+    ModifiedAt(usize, &'a T, Vec<C>),
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum MapChanged<'a, K: 'a, V: 'a> {
+/// `C` is the value's own change type when it derives `Diff`.
+///
+/// With the `serde` feature, serialized as an object tagged by `"op"`:
+/// `{"op": "added_entry" | "removed_entry", "key": k, "value": v}`,
+/// `{"op": "changed_entry", "key": k, "old": v, "new": v}` and
+/// `{"op": "modified_entry", "key": k, "changes": [..]}`. Keys are plain
+/// values, so they need not be strings.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MapChanged<'a, K: 'a, V: 'a, C = ()> {
     AddedEntry(&'a K, &'a V),
     RemovedEntry(&'a K, &'a V),
-    ChangedEntry(&'a K),
+    /// `(key, old value, new value)` of a scalar value.
+    ChangedEntry(&'a K, &'a V, &'a V),
+    /// `(key, nested changes)` of a value that derives `Diff`.
+    ModifiedEntry(&'a K, Vec<C>),
+}
+
+/// Change of an `Option<T>` field; `C` is `T`'s own change type when it
+/// derives `Diff`.
+///
+/// With the `serde` feature: `{"op": "set" | "unset", "value": v}` or
+/// `{"op": "inner", "change": c}`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OptionChanged<'a, T: 'a, C = ()> {
+    /// Now `Some` (was `None`, or held a different scalar value).
+    Set(&'a T),
+    /// Now `None`; carries the old value.
+    Unset(&'a T),
+    /// `Some` on both sides; one nested change of the inner value.
+    Inner(C),
+}
+
+/// Change of one element of a tuple field, named like the `Fields` derive
+/// (`item0`, `item1`, …). `Pn` is the change type of element `n`; unused
+/// positions default to the uninhabited [`Unused`].
+///
+/// With the `serde` feature: `{"item0": change}`, like a derived change.
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TupleChanged<
+    P0,
+    P1 = Unused,
+    P2 = Unused,
+    P3 = Unused,
+    P4 = Unused,
+    P5 = Unused,
+    P6 = Unused,
+    P7 = Unused,
+    P8 = Unused,
+    P9 = Unused,
+    P10 = Unused,
+    P11 = Unused,
+> {
+    item0(P0),
+    item1(P1),
+    item2(P2),
+    item3(P3),
+    item4(P4),
+    item5(P5),
+    item6(P6),
+    item7(P7),
+    item8(P8),
+    item9(P9),
+    item10(P10),
+    item11(P11),
 }
 
-/// Implemented automatically by **`#[derive(Diff)]`**.
+/// Implemented automatically by **`#[derive(Diff)]`**, and by this crate for
+/// std scalars, smart pointers and containers.
 pub trait HasChanges {
     type Change<'a>
     where
         Self: 'a;
 
+    /// `true` for atomic values compared with `PartialEq`, whose only change
+    /// is the new value. Containers report a differing scalar element as
+    /// replaced rather than modified in place.
+    const SCALAR: bool = false;
+
+    /// A hash that agrees with `PartialEq`, for types that have one:
+    /// sequence diffs group equal elements with it in linear time instead of
+    /// comparing them pairwise. Set for the std scalars and
+    /// `serde_json::Value`.
+    const EQ_HASH: Option<fn(&Self) -> u64> = None;
+
     fn collect_changes<'a>(old: &'a Self, new: &'a Self, out: &mut Vec<Self::Change<'a>>)
     where
         Self: 'a;
@@ -39,6 +144,9 @@ pub fn diff_changes<'a, T: HasChanges>(old: &'a T, new: &'a T) -> Vec<T::Change<
 /// `changed!` – flexible, typed diff‑matching macro with zero runtime cost.
 ///
 /// * `@` immediately after the path targets the `self_` variant of the nested change enum.
+/// * `(old => new)` binds both sides of a field marked `#[differs(old_new)]`.
+/// * Paths descend through fields and enum variants alike:
+///   `ConnectionState.Connected.item0.token(t)`.
 ///
 #[macro_export]
 macro_rules! changed {
@@ -53,6 +161,16 @@ macro_rules! changed {
         $( $crate::changed!($change; $($rest)* ); )?
     }};
 
+    (
+        $change:expr;
+        //  `old => new` pair of an `old_new` scalar
+        $ty:ident $( . $path:ident )* ( $old:pat => $new:pat ) => $body:block
+        $( ; $($rest:tt)* )?
+    ) => {{
+        $crate::__changed_arm_old_new! { $change, $ty $( . $path )*, $old, $new, $body }
+        $( $crate::changed!($change; $($rest)* ); )?
+    }};
+
     (
         $change:expr;
         $ty:ident $( . $path:ident )* ( $pat:pat ) => $body:block
@@ -85,7 +203,30 @@ macro_rules! __changed_arm {
     ( $change:expr, $ty:ident . $first:ident . $($tail:ident).+, $pat:pat, $body:block ) => {
         paste::paste! {
             if let [<$ty Change>]::[<$first>](inner) = $change {
-                $crate::changed!(inner; [<$first:camel>] . $($tail).+($pat) => $body );
+                $crate::__changed_inner!( inner, $($tail).+, $pat, $body );
+            }
+        }
+    };
+}
+
+/// Below the first level the nested change type has no name `changed!` can
+/// build (`<Enum><Variant>Change`, or the type of a field), so arms descend
+/// through the hidden `__changed_<variant>` accessors the derive generates.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __changed_inner {
+    // leaf
+    ( $change:expr, $field:ident, $pat:pat, $body:block ) => {
+        paste::paste! {
+            if let ::std::option::Option::Some($pat) = $change.[<__changed_ $field>]() { $body }
+        }
+    };
+
+    // nested chain
+    ( $change:expr, $first:ident . $($tail:ident).+, $pat:pat, $body:block ) => {
+        paste::paste! {
+            if let ::std::option::Option::Some(inner) = $change.[<__changed_ $first>]() {
+                $crate::__changed_inner!( inner, $($tail).+, $pat, $body );
             }
         }
     };
@@ -98,7 +239,7 @@ macro_rules! __changed_arm_at {
     ( $change:expr, $ty:ident . $field:ident, $pat:pat, $body:block ) => {
         paste::paste! {
             if let [<$ty Change>]::$field(inner) = $change {
-                if let [<$field:camel Change>]::self_($pat) = inner { $body }
+                if let ::std::option::Option::Some($pat) = inner.__changed_self_() { $body }
             }
         }
     };
@@ -107,7 +248,27 @@ macro_rules! __changed_arm_at {
     ( $change:expr, $ty:ident . $first:ident . $($tail:ident).+, $pat:pat, $body:block ) => {
         paste::paste! {
             if let [<$ty Change>]::[<$first>](inner) = $change {
-                $crate::__changed_arm_at!( inner, [<$first:camel>] . $($tail).+, $pat, $body );
+                $crate::__changed_inner!( inner, $($tail).+ . self_, $pat, $body );
+            }
+        }
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __changed_arm_old_new {
+    // simple leaf
+    ( $change:expr, $ty:ident . $field:ident, $old:pat, $new:pat, $body:block ) => {
+        paste::paste! {
+            if let [<$ty Change>]::$field($old, $new) = $change { $body }
+        }
+    };
+
+    // nested chain
+    ( $change:expr, $ty:ident . $first:ident . $($tail:ident).+, $old:pat, $new:pat, $body:block ) => {
+        paste::paste! {
+            if let [<$ty Change>]::[<$first>](inner) = $change {
+                $crate::__changed_inner!( inner, $($tail).+, ($old, $new), $body );
             }
         }
     };
@@ -1,6 +1,7 @@
 
 use std::{
     collections::HashMap,
+    fmt::Debug,
     sync::{
         mpsc::{self, Receiver, Sender},
         Arc, Mutex,
@@ -55,6 +56,25 @@ impl FieldName {
             FieldName::from_string(format!("{prefix}.{key}"))
         }
     }
+
+    /// `self.name`, or just `name` at the (empty) root.
+    pub fn child(&self, name: &str) -> Self {
+        if self.0.is_empty() {
+            FieldName(name.to_owned())
+        } else {
+            FieldName(format!("{}.{name}", self.0))
+        }
+    }
+
+    /// `self[idx]`: an element of a sequence.
+    pub fn index(&self, idx: usize) -> Self {
+        FieldName(format!("{}[{idx}]", self.0))
+    }
+
+    /// `self["key"]`: an entry of a map, the key written with `Debug`.
+    pub fn key(&self, key: &(impl Debug + ?Sized)) -> Self {
+        FieldName(format!("{}[{key:?}]", self.0))
+    }
 }
 
 /// Convert something that *represents* a path into a concrete [`FieldName`].
@@ -113,4 +133,135 @@ impl ChangeEventBus {
             list.retain(|tx| tx.send(new_value.clone()).is_ok());
         }
     }
+
+    /// Diff `old` against `new` and publish, once per affected path, the
+    /// JSON of the value now found at that path in `new`, or `null` where
+    /// nothing is left (a removed entry, an index past the end).
+    ///
+    /// Affected paths are those of [`ChangePath`](crate::ChangePath), the
+    /// indices moved elements left, and every ancestor of these up to the
+    /// root (`""`). A subscriber of `Foo::fields().bar()` thus hears about
+    /// any change beneath `bar`, and one of a container that `Fields` only
+    /// reaches as a whole (`.tags()`) about each of its elements
+    /// (`tags[0]`) or entries.
+    ///
+    /// Values are looked up in serde's default representation; renamed
+    /// fields or variants publish `null`.
+    #[cfg(feature = "serde")]
+    pub fn publish_diff<T>(&self, old: &T, new: &T) -> Result<(), serde_json::Error>
+    where
+        T: crate::HasChanges + serde::Serialize,
+        for<'a> T::Change<'a>: crate::ChangePath,
+    {
+        use crate::ChangePath as _;
+        use std::collections::HashSet;
+
+        let changes = crate::diff_changes(old, new);
+        if changes.is_empty() {
+            return Ok(());
+        }
+        let doc = serde_json::to_value(new)?;
+
+        let root = FieldName::static_lit("");
+        let mut paths: Vec<FieldName> = Vec::new();
+        let mut seen: HashSet<FieldName> = HashSet::new();
+        for change in &changes {
+            let mut changed = vec![change.path()];
+            change.vacated_below(&root, &mut changed);
+            for path in changed {
+                for at in ancestors(&path.0) {
+                    let at = FieldName::from_string(at.to_owned());
+                    if seen.insert(at.clone()) {
+                        paths.push(at);
+                    }
+                }
+            }
+        }
+        for path in &paths {
+            let value = serialized_at(&doc, &path.0).unwrap_or(&serde_json::Value::Null);
+            self.publish(&path.0, serde_json::to_string(value)?);
+        }
+        Ok(())
+    }
+}
+
+/// `path` and its ancestors, nearest first: `a.b[3]`, `a.b`, `a`, `""`.
+#[cfg(feature = "serde")]
+fn ancestors(path: &str) -> impl Iterator<Item = &str> {
+    /* a `.` or `[` starts a segment, unless inside a quoted key */
+    let mut cuts = vec![0];
+    let (mut quoted, mut escaped) = (false, false);
+    for (i, c) in path.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if quoted => escaped = true,
+            '"' => quoted = !quoted,
+            '.' | '[' if !quoted && i > 0 => cuts.push(i),
+            _ => {}
+        }
+    }
+    let full = (!path.is_empty()).then_some(path);
+    full.into_iter()
+        .chain(cuts.into_iter().rev().map(move |end| &path[..end]))
+}
+
+/// The part of `doc` at a [`FieldName`] path: fields and variants are object
+/// members, `itemN` an array element (or the value of a newtype), `[n]` an
+/// array element and `[key]` an object member.
+#[cfg(feature = "serde")]
+fn serialized_at<'v>(doc: &'v serde_json::Value, path: &str) -> Option<&'v serde_json::Value> {
+    use serde_json::Value;
+
+    let mut at = doc;
+    let mut rest = path;
+    while !rest.is_empty() {
+        if let Some(inner) = rest.strip_prefix('[') {
+            /* a quoted key may contain `]` */
+            let end = match inner.strip_prefix('"') {
+                Some(quoted) => {
+                    let mut escaped = false;
+                    let close = quoted.char_indices().find_map(|(i, c)| match c {
+                        _ if escaped => {
+                            escaped = false;
+                            None
+                        }
+                        '\\' => {
+                            escaped = true;
+                            None
+                        }
+                        '"' => Some(i),
+                        _ => None,
+                    })?;
+                    close + 2
+                }
+                None => inner.find(']')?,
+            };
+            let segment = &inner[..end];
+            rest = inner[end..].strip_prefix(']')?;
+            at = match at {
+                Value::Array(items) => items.get(segment.parse::<usize>().ok()?)?,
+                Value::Object(members) => {
+                    let key = serde_json::from_str::<String>(segment)
+                        .unwrap_or_else(|_| segment.to_owned());
+                    members.get(&key)?
+                }
+                _ => return None,
+            };
+        } else {
+            let field = rest.strip_prefix('.').unwrap_or(rest);
+            let end = field.find(['.', '[']).unwrap_or(field.len());
+            let (name, tail) = field.split_at(end);
+            rest = tail;
+            let item = name
+                .strip_prefix("item")
+                .and_then(|n| n.parse::<usize>().ok());
+            at = match (at, item) {
+                (Value::Object(members), _) if members.contains_key(name) => &members[name],
+                (Value::Array(items), Some(n)) => items.get(n)?,
+                (value, Some(0)) => value,
+                _ => return None,
+            };
+        }
+    }
+    Some(at)
 }
@@ -0,0 +1,45 @@
+/// Tolerant float comparison backing `#[differs(epsilon = ..)]` and
+/// `#[differs(ulps = ..)]`.
+pub trait FloatEq: Copy {
+    /// `|self - other| <= epsilon`.
+    fn eq_epsilon(self, other: Self, epsilon: f64) -> bool;
+    /// At most `ulps` representable values apart; `0.0 == -0.0`, `NaN`
+    /// equals nothing.
+    fn eq_ulps(self, other: Self, ulps: u64) -> bool;
+}
+
+macro_rules! float_eq {
+    ( $( $f:ty ),* ) => {$(
+        impl FloatEq for $f {
+            fn eq_epsilon(self, other: Self, epsilon: f64) -> bool {
+                self == other || ((self - other).abs() as f64) <= epsilon
+            }
+
+            fn eq_ulps(self, other: Self, ulps: u64) -> bool {
+                if self == other {
+                    return true;
+                }
+                if self.is_nan() || other.is_nan() || self.is_sign_negative() != other.is_sign_negative() {
+                    return false;
+                }
+                /* same sign: the bit patterns are ordered like the values */
+                let (a, b) = (self.to_bits(), other.to_bits());
+                (a.max(b) - a.min(b)) as u64 <= ulps
+            }
+        }
+    )*};
+}
+
+float_eq!(f32, f64);
+
+/// `#[differs(epsilon = ..)]` comparison.
+#[inline]
+pub fn eq_epsilon<F: FloatEq>(old: &F, new: &F, epsilon: f64) -> bool {
+    old.eq_epsilon(*new, epsilon)
+}
+
+/// `#[differs(ulps = ..)]` comparison.
+#[inline]
+pub fn eq_ulps<F: FloatEq>(old: &F, new: &F, ulps: u64) -> bool {
+    old.eq_ulps(*new, ulps)
+}
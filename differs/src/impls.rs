@@ -0,0 +1,498 @@
+//! `HasChanges` for std scalars, smart pointers and containers.
+
+use std::{
+    borrow::{Cow, ToOwned},
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque},
+    fmt::Debug,
+    hash::{BuildHasher, DefaultHasher, Hash, Hasher},
+    marker::PhantomData,
+    rc::Rc,
+    sync::Arc,
+};
+
+use crate::{
+    myers_lcs, ordered_changes, seq::ordered_ref_changes, Changed, HasChanges, MapChanged,
+    OptionChanged, TupleChanged, Unused,
+};
+
+/* ------------------------------------------------------------------------- */
+/* Scalars                                                                   */
+/* ------------------------------------------------------------------------- */
+
+/// [`HasChanges::EQ_HASH`] of a `Hash + Eq` type.
+pub(crate) fn eq_hash<T: Hash + ?Sized>(value: &T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Atomic values: the change is the new value.
+macro_rules! scalar_impls {
+    ( $( $ty:ty ),* $(,)? ) => {$(
+        impl HasChanges for $ty {
+            type Change<'a> = &'a $ty;
+            const SCALAR: bool = true;
+            const EQ_HASH: Option<fn(&Self) -> u64> = Some(eq_hash::<$ty>);
+
+            fn collect_changes<'a>(old: &'a Self, new: &'a Self, out: &mut Vec<Self::Change<'a>>)
+            where
+                Self: 'a,
+            {
+                if old != new {
+                    out.push(new);
+                }
+            }
+        }
+    )*};
+}
+
+scalar_impls!(
+    u8,
+    u16,
+    u32,
+    u64,
+    u128,
+    usize,
+    i8,
+    i16,
+    i32,
+    i64,
+    i128,
+    isize,
+    bool,
+    char,
+    (),
+    std::num::NonZeroU8,
+    std::num::NonZeroU16,
+    std::num::NonZeroU32,
+    std::num::NonZeroU64,
+    std::num::NonZeroU128,
+    std::num::NonZeroUsize,
+    std::num::NonZeroI8,
+    std::num::NonZeroI16,
+    std::num::NonZeroI32,
+    std::num::NonZeroI64,
+    std::num::NonZeroI128,
+    std::num::NonZeroIsize,
+    std::time::Duration,
+    std::time::Instant,
+    std::time::SystemTime,
+    std::path::PathBuf,
+    std::path::Path,
+    std::ffi::OsString,
+    std::ffi::OsStr,
+    std::net::IpAddr,
+    std::net::Ipv4Addr,
+    std::net::Ipv6Addr,
+    std::net::SocketAddr,
+    std::net::SocketAddrV4,
+    std::net::SocketAddrV6,
+    std::cmp::Ordering,
+);
+
+/// Floats hash by value, `-0.0` like `0.0`; `NaN` equals nothing anyway.
+macro_rules! float_impls {
+    ( $( $ty:ty ),* ) => {$(
+        impl HasChanges for $ty {
+            type Change<'a> = &'a $ty;
+            const SCALAR: bool = true;
+            const EQ_HASH: Option<fn(&Self) -> u64> = Some(|v| eq_hash(&(v + 0.0).to_bits()));
+
+            fn collect_changes<'a>(old: &'a Self, new: &'a Self, out: &mut Vec<Self::Change<'a>>)
+            where
+                Self: 'a,
+            {
+                if old != new {
+                    out.push(new);
+                }
+            }
+        }
+    )*};
+}
+
+float_impls!(f32, f64);
+
+/// Strings report a `Cow`, like `String` fields of derived types.
+macro_rules! string_impls {
+    ( $( $ty:ty ),* ) => {$(
+        impl HasChanges for $ty {
+            type Change<'a> = Cow<'a, str>;
+            const SCALAR: bool = true;
+            const EQ_HASH: Option<fn(&Self) -> u64> = Some(eq_hash::<$ty>);
+
+            fn collect_changes<'a>(old: &'a Self, new: &'a Self, out: &mut Vec<Self::Change<'a>>)
+            where
+                Self: 'a,
+            {
+                if old != new {
+                    out.push(Cow::Borrowed(new));
+                }
+            }
+        }
+    )*};
+}
+
+string_impls!(String, str);
+
+/// Markers hold no data and never change.
+impl<T: ?Sized> HasChanges for PhantomData<T> {
+    type Change<'a>
+        = Unused
+    where
+        Self: 'a;
+    const SCALAR: bool = true;
+    const EQ_HASH: Option<fn(&Self) -> u64> = Some(|_| 0);
+
+    fn collect_changes<'a>(_: &'a Self, _: &'a Self, _: &mut Vec<Self::Change<'a>>)
+    where
+        Self: 'a,
+    {
+    }
+}
+
+/* ------------------------------------------------------------------------- */
+/* References and smart pointers: transparent                                */
+/* ------------------------------------------------------------------------- */
+
+macro_rules! pointer_impls {
+    ( $( $ptr:ident ),* ) => {$(
+        impl<T: HasChanges + ?Sized> HasChanges for $ptr<T> {
+            type Change<'a> = T::Change<'a> where Self: 'a;
+            const SCALAR: bool = T::SCALAR;
+
+            fn collect_changes<'a>(old: &'a Self, new: &'a Self, out: &mut Vec<Self::Change<'a>>)
+            where
+                Self: 'a,
+            {
+                T::collect_changes(old, new, out)
+            }
+        }
+    )*};
+}
+
+pointer_impls!(Box, Rc, Arc);
+
+impl<T: HasChanges + ?Sized> HasChanges for &T {
+    type Change<'a>
+        = T::Change<'a>
+    where
+        Self: 'a;
+    const SCALAR: bool = T::SCALAR;
+
+    fn collect_changes<'a>(old: &'a Self, new: &'a Self, out: &mut Vec<Self::Change<'a>>)
+    where
+        Self: 'a,
+    {
+        T::collect_changes(*old, *new, out)
+    }
+}
+
+impl<B: HasChanges + ToOwned + ?Sized> HasChanges for Cow<'_, B> {
+    type Change<'a>
+        = B::Change<'a>
+    where
+        Self: 'a;
+    const SCALAR: bool = B::SCALAR;
+
+    fn collect_changes<'a>(old: &'a Self, new: &'a Self, out: &mut Vec<Self::Change<'a>>)
+    where
+        Self: 'a,
+    {
+        B::collect_changes(old, new, out)
+    }
+}
+
+/* ------------------------------------------------------------------------- */
+/* Option                                                                    */
+/* ------------------------------------------------------------------------- */
+
+impl<T: HasChanges + Debug> HasChanges for Option<T> {
+    type Change<'a>
+        = OptionChanged<'a, T, T::Change<'a>>
+    where
+        Self: 'a;
+
+    fn collect_changes<'a>(old: &'a Self, new: &'a Self, out: &mut Vec<Self::Change<'a>>)
+    where
+        Self: 'a,
+    {
+        match (old, new) {
+            (None, Some(nv)) => out.push(OptionChanged::Set(nv)),
+            (Some(ov), None) => out.push(OptionChanged::Unset(ov)),
+            (Some(ov), Some(nv)) => {
+                let mut subs = Vec::new();
+                T::collect_changes(ov, nv, &mut subs);
+                if T::SCALAR {
+                    if !subs.is_empty() {
+                        out.push(OptionChanged::Set(nv));
+                    }
+                } else {
+                    out.extend(subs.into_iter().map(OptionChanged::Inner));
+                }
+            }
+            (None, None) => {}
+        }
+    }
+}
+
+/* ------------------------------------------------------------------------- */
+/* Sequences: Myers edit script                                              */
+/* ------------------------------------------------------------------------- */
+
+impl<T: HasChanges + PartialEq + Debug> HasChanges for Vec<T> {
+    type Change<'a>
+        = Changed<'a, T, T::Change<'a>>
+    where
+        Self: 'a;
+
+    fn collect_changes<'a>(old: &'a Self, new: &'a Self, out: &mut Vec<Self::Change<'a>>)
+    where
+        Self: 'a,
+    {
+        /* scalar elements are replaced, never modified in place */
+        let nested = (!T::SCALAR).then_some(crate::diff_changes::<T> as fn(_, _) -> _);
+        let matched = myers_lcs(old, new);
+        out.extend(ordered_changes(old, new, &matched, nested, T::EQ_HASH));
+    }
+}
+
+impl<T: HasChanges + PartialEq + Debug> HasChanges for VecDeque<T> {
+    type Change<'a>
+        = Changed<'a, T, T::Change<'a>>
+    where
+        Self: 'a;
+
+    fn collect_changes<'a>(old: &'a Self, new: &'a Self, out: &mut Vec<Self::Change<'a>>)
+    where
+        Self: 'a,
+    {
+        let old: Vec<&T> = old.iter().collect();
+        let new: Vec<&T> = new.iter().collect();
+        let nested = (!T::SCALAR).then_some(crate::diff_changes::<T> as fn(_, _) -> _);
+        let matched = myers_lcs(&old, &new);
+        out.extend(ordered_ref_changes(
+            &old,
+            &new,
+            &matched,
+            nested,
+            T::EQ_HASH,
+        ));
+    }
+}
+
+/// Positional: every differing slot is a `ModifiedAt`.
+impl<T: HasChanges + Debug, const N: usize> HasChanges for [T; N] {
+    type Change<'a>
+        = Changed<'a, T, T::Change<'a>>
+    where
+        Self: 'a;
+
+    fn collect_changes<'a>(old: &'a Self, new: &'a Self, out: &mut Vec<Self::Change<'a>>)
+    where
+        Self: 'a,
+    {
+        for (i, (ov, nv)) in old.iter().zip(new).enumerate() {
+            let mut subs = crate::diff_changes(ov, nv);
+            if !subs.is_empty() {
+                /* replaced scalars carry no nested changes */
+                if T::SCALAR {
+                    subs.clear();
+                }
+                out.push(Changed::ModifiedAt(i, nv, subs));
+            }
+        }
+    }
+}
+
+/* ------------------------------------------------------------------------- */
+/* Sets and maps                                                             */
+/* ------------------------------------------------------------------------- */
+
+impl<T: Hash + Eq + Debug, S: BuildHasher> HasChanges for HashSet<T, S> {
+    type Change<'a>
+        = Changed<'a, T>
+    where
+        Self: 'a;
+
+    fn collect_changes<'a>(old: &'a Self, new: &'a Self, out: &mut Vec<Self::Change<'a>>)
+    where
+        Self: 'a,
+    {
+        out.extend(old.difference(new).map(Changed::Removed));
+        out.extend(new.difference(old).map(Changed::Added));
+    }
+}
+
+impl<T: Ord + Debug> HasChanges for BTreeSet<T> {
+    type Change<'a>
+        = Changed<'a, T>
+    where
+        Self: 'a;
+
+    fn collect_changes<'a>(old: &'a Self, new: &'a Self, out: &mut Vec<Self::Change<'a>>)
+    where
+        Self: 'a,
+    {
+        /* one pass in ascending order */
+        for v in old.symmetric_difference(new) {
+            out.push(if old.contains(v) {
+                Changed::Removed(v)
+            } else {
+                Changed::Added(v)
+            });
+        }
+    }
+}
+
+/// Both values present under `k`: `ChangedEntry` for scalars, otherwise one
+/// `ModifiedEntry` with the nested changes.
+fn entry_changes<'a, K, V: HasChanges>(
+    k: &'a K,
+    ov: &'a V,
+    nv: &'a V,
+    out: &mut Vec<MapChanged<'a, K, V, V::Change<'a>>>,
+) {
+    let subs = crate::diff_changes(ov, nv);
+    if subs.is_empty() {
+        return;
+    }
+    out.push(if V::SCALAR {
+        MapChanged::ChangedEntry(k, ov, nv)
+    } else {
+        MapChanged::ModifiedEntry(k, subs)
+    });
+}
+
+impl<K: Hash + Eq + Debug, V: HasChanges + Debug, S: BuildHasher> HasChanges for HashMap<K, V, S> {
+    type Change<'a>
+        = MapChanged<'a, K, V, V::Change<'a>>
+    where
+        Self: 'a;
+
+    fn collect_changes<'a>(old: &'a Self, new: &'a Self, out: &mut Vec<Self::Change<'a>>)
+    where
+        Self: 'a,
+    {
+        for (k, ov) in old {
+            match new.get(k) {
+                None => out.push(MapChanged::RemovedEntry(k, ov)),
+                Some(nv) => entry_changes(k, ov, nv, out),
+            }
+        }
+        for (k, nv) in new {
+            if !old.contains_key(k) {
+                out.push(MapChanged::AddedEntry(k, nv));
+            }
+        }
+    }
+}
+
+impl<K: Ord + Debug, V: HasChanges + Debug> HasChanges for BTreeMap<K, V> {
+    type Change<'a>
+        = MapChanged<'a, K, V, V::Change<'a>>
+    where
+        Self: 'a;
+
+    fn collect_changes<'a>(old: &'a Self, new: &'a Self, out: &mut Vec<Self::Change<'a>>)
+    where
+        Self: 'a,
+    {
+        /* merged walk over both key sets, in ascending order */
+        let keys: BTreeSet<&K> = old.keys().chain(new.keys()).collect();
+        for k in keys {
+            match (old.get(k), new.get(k)) {
+                (Some(ov), None) => out.push(MapChanged::RemovedEntry(k, ov)),
+                (None, Some(nv)) => out.push(MapChanged::AddedEntry(k, nv)),
+                (Some(ov), Some(nv)) => entry_changes(k, ov, nv, out),
+                (None, None) => {}
+            }
+        }
+    }
+}
+
+#[cfg(feature = "indexmap")]
+impl<T: Hash + Eq + Debug, S: BuildHasher> HasChanges for indexmap::IndexSet<T, S> {
+    type Change<'a>
+        = Changed<'a, T>
+    where
+        Self: 'a;
+
+    fn collect_changes<'a>(old: &'a Self, new: &'a Self, out: &mut Vec<Self::Change<'a>>)
+    where
+        Self: 'a,
+    {
+        out.extend(old.difference(new).map(Changed::Removed));
+        out.extend(new.difference(old).map(Changed::Added));
+    }
+}
+
+#[cfg(feature = "indexmap")]
+impl<K: Hash + Eq + Debug, V: HasChanges + Debug, S: BuildHasher> HasChanges
+    for indexmap::IndexMap<K, V, S>
+{
+    type Change<'a>
+        = MapChanged<'a, K, V, V::Change<'a>>
+    where
+        Self: 'a;
+
+    fn collect_changes<'a>(old: &'a Self, new: &'a Self, out: &mut Vec<Self::Change<'a>>)
+    where
+        Self: 'a,
+    {
+        for (k, ov) in old {
+            match new.get(k) {
+                None => out.push(MapChanged::RemovedEntry(k, ov)),
+                Some(nv) => entry_changes(k, ov, nv, out),
+            }
+        }
+        for (k, nv) in new {
+            if !old.contains_key(k) {
+                out.push(MapChanged::AddedEntry(k, nv));
+            }
+        }
+    }
+}
+
+/* ------------------------------------------------------------------------- */
+/* Tuples: element-wise, `item0`, `item1`, …                                 */
+/* ------------------------------------------------------------------------- */
+
+macro_rules! tuple_impls {
+    ( $( ( $( $t:ident $idx:tt $item:ident ),+ ) )* ) => {$(
+        impl<$( $t: HasChanges ),+> HasChanges for ( $( $t, )+ ) {
+            type Change<'a> = TupleChanged<$( $t::Change<'a> ),+> where Self: 'a;
+
+            fn collect_changes<'a>(old: &'a Self, new: &'a Self, out: &mut Vec<Self::Change<'a>>)
+            where
+                Self: 'a,
+            {
+                $(
+                    out.extend(
+                        crate::diff_changes(&old.$idx, &new.$idx)
+                            .into_iter()
+                            .map(TupleChanged::$item),
+                    );
+                )+
+            }
+        }
+    )*};
+}
+
+tuple_impls! {
+    (A 0 item0)
+    (A 0 item0, B 1 item1)
+    (A 0 item0, B 1 item1, C 2 item2)
+    (A 0 item0, B 1 item1, C 2 item2, D 3 item3)
+    (A 0 item0, B 1 item1, C 2 item2, D 3 item3, E 4 item4)
+    (A 0 item0, B 1 item1, C 2 item2, D 3 item3, E 4 item4, F 5 item5)
+    (A 0 item0, B 1 item1, C 2 item2, D 3 item3, E 4 item4, F 5 item5, G 6 item6)
+    (A 0 item0, B 1 item1, C 2 item2, D 3 item3, E 4 item4, F 5 item5, G 6 item6, H 7 item7)
+    (A 0 item0, B 1 item1, C 2 item2, D 3 item3, E 4 item4, F 5 item5, G 6 item6, H 7 item7,
+     I 8 item8)
+    (A 0 item0, B 1 item1, C 2 item2, D 3 item3, E 4 item4, F 5 item5, G 6 item6, H 7 item7,
+     I 8 item8, J 9 item9)
+    (A 0 item0, B 1 item1, C 2 item2, D 3 item3, E 4 item4, F 5 item5, G 6 item6, H 7 item7,
+     I 8 item8, J 9 item9, K 10 item10)
+    (A 0 item0, B 1 item1, C 2 item2, D 3 item3, E 4 item4, F 5 item5, G 6 item6, H 7 item7,
+     I 8 item8, J 9 item9, K 10 item10, L 11 item11)
+}
@@ -0,0 +1,344 @@
+//! RFC 6902 JSON Patch documents from change sets (`serde` feature).
+
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, BTreeSet},
+};
+
+use serde::{ser::Error as _, Serialize};
+use serde_json::{json, Error, Value};
+
+use crate::{Changed, MapChanged, OptionChanged, TupleChanged, Unused};
+
+/// Change types that can be written as JSON Patch operations on the `serde`
+/// form of the diffed value.
+///
+/// Implemented for the library's change types and, with
+/// `#[differs(serde)]`, for the `<Name>Change` enums of a derive. Derived
+/// paths use the Rust field and variant names and serde's default (externally
+/// tagged) enum representation.
+pub trait JsonPatchOps: Sized {
+    /// Appends the operations of `changes` (all changes of one value, in
+    /// diff order) to `out`, with paths below the JSON Pointer `at`.
+    fn json_patch_ops(changes: &[&Self], at: &str, out: &mut Vec<Value>) -> Result<(), Error>;
+}
+
+/// The RFC 6902 document (a JSON array of operations) turning the `serde`
+/// form of `old` into that of `new`, from `diff_changes(&old, &new)`.
+///
+/// Fails when a value does not serialize, a map key is not a string, number
+/// or bool, or a set lost an element (a set has no index to remove it at).
+pub fn json_patch<C: JsonPatchOps>(changes: &[C]) -> Result<Value, Error> {
+    let changes: Vec<&C> = changes.iter().collect();
+    let mut out = Vec::new();
+    C::json_patch_ops(&changes, "", &mut out)?;
+    Ok(Value::Array(out))
+}
+
+/// `op` on `path`, with a serialized `value`.
+fn op_value(op: &str, path: &str, value: &(impl Serialize + ?Sized)) -> Result<Value, Error> {
+    Ok(json!({ "op": op, "path": path, "value": serde_json::to_value(value)? }))
+}
+
+/// One JSON Pointer reference token (RFC 6901 escaping).
+fn escape(segment: &str) -> String {
+    segment.replace('~', "~0").replace('/', "~1")
+}
+
+/* ------------------------------------------------------------------------- */
+/* Helpers shared with the derive                                            */
+/* ------------------------------------------------------------------------- */
+
+/// Functions `#[differs(serde)]` expands to, re-exported from `__private`.
+pub(crate) mod glue {
+    use super::*;
+
+    /// Writes the changes `pick` selects from `changes`, if any, below `at`.
+    pub fn json_part<'c, P, C: JsonPatchOps + 'c>(
+        changes: &[&'c P],
+        pick: impl Fn(&'c P) -> Option<&'c C>,
+        at: String,
+        out: &mut Vec<Value>,
+    ) -> Result<(), Error> {
+        let part: Vec<&C> = changes.iter().filter_map(|c| pick(c)).collect();
+        if part.is_empty() {
+            return Ok(());
+        }
+        C::json_patch_ops(&part, &at, out)
+    }
+
+    /// Replaces the whole value at `at`.
+    pub fn json_replace(
+        value: &(impl Serialize + ?Sized),
+        at: &str,
+        out: &mut Vec<Value>,
+    ) -> Result<(), Error> {
+        out.push(op_value("replace", at, value)?);
+        Ok(())
+    }
+}
+
+use glue::*;
+
+/* ------------------------------------------------------------------------- */
+/* Scalars: the new value replaces the old                                   */
+/* ------------------------------------------------------------------------- */
+
+impl<T: Serialize + ?Sized> JsonPatchOps for &T {
+    fn json_patch_ops(changes: &[&Self], at: &str, out: &mut Vec<Value>) -> Result<(), Error> {
+        match changes.last() {
+            Some(v) => json_replace(**v, at, out),
+            None => Ok(()),
+        }
+    }
+}
+
+impl JsonPatchOps for Cow<'_, str> {
+    fn json_patch_ops(changes: &[&Self], at: &str, out: &mut Vec<Value>) -> Result<(), Error> {
+        match changes.last() {
+            Some(v) => json_replace(v.as_ref(), at, out),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Nested change type of a container whose elements do not derive `Diff`.
+impl JsonPatchOps for () {
+    fn json_patch_ops(_: &[&Self], _: &str, _: &mut Vec<Value>) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl JsonPatchOps for Unused {
+    fn json_patch_ops(_: &[&Self], _: &str, _: &mut Vec<Value>) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+/* ------------------------------------------------------------------------- */
+/* Sequences and sets                                                        */
+/* ------------------------------------------------------------------------- */
+
+/// An element of the array being patched.
+#[derive(Clone, Copy, PartialEq)]
+enum Slot {
+    /// Untouched or moved, by old index.
+    Old(usize),
+    /// Added, by new index.
+    New(usize),
+}
+
+/// The start of the array as the operations so far left it, grown lazily:
+/// everything past it is still in its old order.
+struct Model {
+    slots: Vec<Slot>,
+    next_old: usize,
+}
+
+impl Model {
+    fn pos(&mut self, slot: Slot) -> usize {
+        if let Slot::Old(idx) = slot {
+            while self.next_old <= idx {
+                self.slots.push(Slot::Old(self.next_old));
+                self.next_old += 1;
+            }
+        }
+        self.slots
+            .iter()
+            .position(|s| *s == slot)
+            .expect("slot is in the model")
+    }
+}
+
+/// Operations apply one after the other, so indices are rewritten against a
+/// model of the array: removals first, then every added or moved element
+/// goes right behind its new predecessor, in new-index order. `ModifiedAt`
+/// changes then address the finished array.
+impl<T: Serialize, C: JsonPatchOps> JsonPatchOps for Changed<'_, T, C> {
+    fn json_patch_ops(changes: &[&Self], at: &str, out: &mut Vec<Value>) -> Result<(), Error> {
+        let mut removed = Vec::new();
+        let mut placed: BTreeMap<usize, (Slot, Option<&T>)> = BTreeMap::new();
+        let mut modified = Vec::new();
+        for change in changes {
+            match **change {
+                Changed::Added(v) => out.push(op_value("add", &format!("{at}/-"), v)?),
+                Changed::Removed(_) => {
+                    return Err(Error::custom(format!(
+                        "`{at}`: a set removal has no JSON Patch index"
+                    )));
+                }
+                Changed::AddedAt(idx, v, _) => {
+                    placed.insert(idx, (Slot::New(idx), Some(v)));
+                }
+                Changed::AddedRange(idx, vs) => {
+                    for (k, v) in vs.iter().enumerate() {
+                        placed.insert(idx + k, (Slot::New(idx + k), Some(v)));
+                    }
+                }
+                Changed::RemovedAt(idx, ..) => removed.push(idx),
+                Changed::RemovedRange(idx, vs) => removed.extend(idx..idx + vs.len()),
+                Changed::Moved(_, from, to) => {
+                    placed.insert(to, (Slot::Old(from), None));
+                }
+                Changed::ModifiedAt(idx, v, ref subs) => modified.push((idx, v, subs)),
+            }
+        }
+
+        let consumed: BTreeSet<usize> = removed
+            .iter()
+            .copied()
+            .chain(placed.values().filter_map(|(slot, _)| match slot {
+                Slot::Old(from) => Some(*from),
+                Slot::New(_) => None,
+            }))
+            .collect();
+        let mut model = Model {
+            slots: Vec::new(),
+            next_old: 0,
+        };
+        for idx in removed {
+            let pos = model.pos(Slot::Old(idx));
+            model.slots.remove(pos);
+            out.push(json!({ "op": "remove", "path": format!("{at}/{pos}") }));
+        }
+
+        /* element at new index `idx`: placed, or the next untouched one */
+        let final_slot = |idx: usize| match placed.get(&idx) {
+            Some((slot, _)) => *slot,
+            None => {
+                let rank = idx - placed.range(..idx).count();
+                let old = (0..)
+                    .filter(|i| !consumed.contains(i))
+                    .nth(rank)
+                    .expect("unbounded");
+                Slot::Old(old)
+            }
+        };
+        for (&idx, &(slot, value)) in &placed {
+            let from = match slot {
+                Slot::Old(_) => {
+                    let pos = model.pos(slot);
+                    model.slots.remove(pos);
+                    Some(pos)
+                }
+                Slot::New(_) => None,
+            };
+            let pos = match idx {
+                0 => 0,
+                _ => model.pos(final_slot(idx - 1)) + 1,
+            };
+            model.slots.insert(pos, slot);
+            match (from, value) {
+                (Some(from), _) if from == pos => {}
+                (Some(from), _) => out.push(json!({
+                    "op": "move",
+                    "from": format!("{at}/{from}"),
+                    "path": format!("{at}/{pos}"),
+                })),
+                (None, value) => out.push(op_value("add", &format!("{at}/{pos}"), &value)?),
+            }
+        }
+
+        /* replaced scalars carry no nested changes */
+        for (idx, v, subs) in modified {
+            let at = format!("{at}/{idx}");
+            if subs.is_empty() {
+                json_replace(v, &at, out)?;
+            } else {
+                C::json_patch_ops(&subs.iter().collect::<Vec<_>>(), &at, out)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/* ------------------------------------------------------------------------- */
+/* Maps, options, tuples                                                     */
+/* ------------------------------------------------------------------------- */
+
+/// Path segment of a map key, as `serde_json` writes object keys.
+fn key_segment(key: &impl Serialize) -> Result<String, Error> {
+    match serde_json::to_value(key)? {
+        Value::String(s) => Ok(escape(&s)),
+        Value::Number(n) => Ok(n.to_string()),
+        Value::Bool(b) => Ok(b.to_string()),
+        _ => Err(Error::custom(
+            "map keys must serialize to strings, numbers or bools",
+        )),
+    }
+}
+
+impl<K: Serialize, V: Serialize, C: JsonPatchOps> JsonPatchOps for MapChanged<'_, K, V, C> {
+    fn json_patch_ops(changes: &[&Self], at: &str, out: &mut Vec<Value>) -> Result<(), Error> {
+        for change in changes {
+            match change {
+                MapChanged::AddedEntry(k, v) => {
+                    out.push(op_value("add", &format!("{at}/{}", key_segment(k)?), *v)?);
+                }
+                MapChanged::RemovedEntry(k, _) => {
+                    out.push(
+                        json!({ "op": "remove", "path": format!("{at}/{}", key_segment(k)?) }),
+                    );
+                }
+                MapChanged::ChangedEntry(k, _, v) => {
+                    json_replace(*v, &format!("{at}/{}", key_segment(k)?), out)?;
+                }
+                MapChanged::ModifiedEntry(k, subs) => C::json_patch_ops(
+                    &subs.iter().collect::<Vec<_>>(),
+                    &format!("{at}/{}", key_segment(k)?),
+                    out,
+                )?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// `None` is `null` in the `serde` form, so setting and unsetting replace.
+impl<T: Serialize, C: JsonPatchOps> JsonPatchOps for OptionChanged<'_, T, C> {
+    fn json_patch_ops(changes: &[&Self], at: &str, out: &mut Vec<Value>) -> Result<(), Error> {
+        let mut inner = Vec::new();
+        for change in changes {
+            match change {
+                OptionChanged::Set(v) => json_replace(*v, at, out)?,
+                OptionChanged::Unset(_) => json_replace(&Value::Null, at, out)?,
+                OptionChanged::Inner(c) => inner.push(c),
+            }
+        }
+        if inner.is_empty() {
+            return Ok(());
+        }
+        C::json_patch_ops(&inner, at, out)
+    }
+}
+
+macro_rules! tuple_json_patch {
+    ( $( $p:ident $idx:tt $item:ident ),* ) => {
+        /// Element `n` lives at `/n` of the `serde` array.
+        impl<$( $p: JsonPatchOps ),*> JsonPatchOps for TupleChanged<$( $p ),*> {
+            fn json_patch_ops(
+                changes: &[&Self],
+                at: &str,
+                out: &mut Vec<Value>,
+            ) -> Result<(), Error> {
+                $(
+                    json_part(
+                        changes,
+                        |c| match c {
+                            TupleChanged::$item(c) => Some(c),
+                            _ => None,
+                        },
+                        format!("{at}/{}", $idx),
+                        out,
+                    )?;
+                )*
+                Ok(())
+            }
+        }
+    };
+}
+
+tuple_json_patch!(
+    P0 0 item0, P1 1 item1, P2 2 item2, P3 3 item3, P4 4 item4, P5 5 item5,
+    P6 6 item6, P7 7 item7, P8 8 item8, P9 9 item9, P10 10 item10, P11 11 item11
+);
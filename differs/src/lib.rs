@@ -3,8 +3,73 @@ extern crate self as differs;
 mod field_paths;
 pub use field_paths::*;
 
+mod change_path;
+pub use change_path::*;
+
 pub use differs_derive::Diff;
 pub use differs_derive::Fields;
 
 mod changed;
 pub use changed::*;
+
+mod seq;
+pub use seq::*;
+
+mod float;
+pub use float::*;
+
+mod owned;
+pub use owned::*;
+
+mod patch;
+pub use patch::{apply_changes, ApplyChanges, PatchError, PatchErrorKind};
+
+mod impls;
+
+mod value;
+pub use value::*;
+
+#[cfg(feature = "serde")]
+mod wire;
+
+#[cfg(feature = "serde")]
+mod json_patch;
+#[cfg(feature = "serde")]
+pub use json_patch::{json_patch, JsonPatchOps};
+
+#[cfg(feature = "serde")]
+mod merge_patch;
+#[cfg(feature = "serde")]
+pub use merge_patch::{apply_merge_patch, merge_patch};
+
+/// Wraps the expansion of `#[derive(Diff)]` on a `#[differs(serde)]` type.
+#[cfg(feature = "serde")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __require_serde {
+    ($($expanded:tt)*) => { $($expanded)* };
+}
+
+/// Wraps the expansion of `#[derive(Diff)]` on a `#[differs(serde)]` type.
+#[cfg(not(feature = "serde"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __require_serde {
+    ($($expanded:tt)*) => {
+        compile_error!("#[differs(serde)] requires the `serde` feature of differs");
+    };
+}
+
+/// Paths the derive macros expand to; not public API.
+#[doc(hidden)]
+pub mod __private {
+    #[cfg(feature = "serde")]
+    pub use serde;
+    #[cfg(feature = "serde")]
+    pub use serde_json;
+
+    #[cfg(feature = "serde")]
+    pub use crate::json_patch::glue::*;
+
+    pub use crate::patch::glue::*;
+}
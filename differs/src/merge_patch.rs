@@ -0,0 +1,86 @@
+//! RFC 7386 JSON Merge Patch (`serde` feature).
+//!
+//! Merge patches work on the `serde` form of a value: objects merge member
+//! by member, `null` deletes a member, anything else (arrays included)
+//! replaces the target whole. Change sets cannot rebuild a whole array, so
+//! patches come from the serialized values rather than from `diff_changes`.
+
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{Error, Map, Value};
+
+use crate::{diff_changes, HasChanges, IntoOwned};
+
+/// The minimal merge patch turning the `serde` form of `old` into that of
+/// `new`: nested objects for changed struct fields, `null` for removed map
+/// entries and unset options, and `{}` when nothing changed.
+///
+/// A member that is `null` in `new` but absent from `old` cannot be
+/// expressed and is left out; `serde` reads both back as `None`.
+pub fn merge_patch<T: Serialize + ?Sized>(old: &T, new: &T) -> Result<Value, Error> {
+    let old = serde_json::to_value(old)?;
+    let new = serde_json::to_value(new)?;
+    Ok(merge_diff(&old, &new).unwrap_or_else(|| Value::Object(Map::new())))
+}
+
+/// Applies the merge patch `patch` to `target` through its `serde` form and
+/// returns the owned changes (`#[differs(owned)]`) between the old and the
+/// patched value. `target` is left as it was when the patched form does not
+/// deserialize.
+pub fn apply_merge_patch<T, O>(target: &mut T, patch: &Value) -> Result<Vec<O>, Error>
+where
+    T: HasChanges + Serialize + DeserializeOwned + 'static,
+    for<'a> T::Change<'a>: IntoOwned<Owned = O>,
+{
+    let mut doc = serde_json::to_value(&*target)?;
+    merge(&mut doc, patch);
+    let old = std::mem::replace(target, serde_json::from_value(doc)?);
+    Ok(diff_changes(&old, target)
+        .into_iter()
+        .map(IntoOwned::into_owned)
+        .collect())
+}
+
+/// RFC 7386 `MergePatch(target, patch)`, in place.
+fn merge(target: &mut Value, patch: &Value) {
+    let Value::Object(patch) = patch else {
+        *target = patch.clone();
+        return;
+    };
+    if !target.is_object() {
+        *target = Value::Object(Map::new());
+    }
+    let Value::Object(target) = target else {
+        unreachable!("just made an object");
+    };
+    for (key, value) in patch {
+        if value.is_null() {
+            target.remove(key);
+        } else {
+            merge(target.entry(key.as_str()).or_insert(Value::Null), value);
+        }
+    }
+}
+
+/// `None` when `old` and `new` are equal.
+fn merge_diff(old: &Value, new: &Value) -> Option<Value> {
+    match (old, new) {
+        (Value::Object(old), Value::Object(new)) => {
+            let mut patch = Map::new();
+            for key in old.keys().filter(|k| !new.contains_key(*k)) {
+                patch.insert(key.clone(), Value::Null);
+            }
+            for (key, value) in new {
+                let change = match old.get(key) {
+                    Some(prev) => merge_diff(prev, value),
+                    None => (!value.is_null()).then(|| value.clone()),
+                };
+                if let Some(change) = change {
+                    patch.insert(key.clone(), change);
+                }
+            }
+            (!patch.is_empty()).then_some(Value::Object(patch))
+        }
+        _ if old == new => None,
+        _ => Some(new.clone()),
+    }
+}
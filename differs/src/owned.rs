@@ -0,0 +1,222 @@
+//! `'static` counterparts of the borrowed change types.
+
+use std::{borrow::Cow, fmt::Debug, marker::PhantomData};
+
+use crate::{Changed, MapChanged, OptionChanged, TupleChanged, Unused};
+
+/// Detaches a change from the values it was computed from, so it can be
+/// stored, sent to another thread or outlive `old` / `new`.
+///
+/// Implemented for the library's change types and, with
+/// `#[differs(owned)]`, for the `<Name>Change` enums of a derive.
+pub trait IntoOwned {
+    type Owned: Clone + Debug + 'static;
+
+    fn into_owned(self) -> Self::Owned;
+}
+
+impl<T: ToOwned + ?Sized> IntoOwned for &T
+where
+    T::Owned: Clone + Debug + 'static,
+{
+    type Owned = T::Owned;
+
+    fn into_owned(self) -> Self::Owned {
+        self.to_owned()
+    }
+}
+
+impl<B: ToOwned + ?Sized> IntoOwned for Cow<'_, B>
+where
+    B::Owned: Clone + Debug + 'static,
+{
+    type Owned = <B as ToOwned>::Owned;
+
+    fn into_owned(self) -> <B as ToOwned>::Owned {
+        Cow::into_owned(self)
+    }
+}
+
+impl<C: IntoOwned> IntoOwned for Vec<C> {
+    type Owned = Vec<C::Owned>;
+
+    fn into_owned(self) -> Self::Owned {
+        self.into_iter().map(C::into_owned).collect()
+    }
+}
+
+impl IntoOwned for Unused {
+    type Owned = Unused;
+
+    fn into_owned(self) -> Self::Owned {
+        match self {}
+    }
+}
+
+/// Values that already own their data.
+macro_rules! owned_values {
+    ( $( $ty:ty ),* $(,)? ) => {$(
+        impl IntoOwned for $ty {
+            type Owned = $ty;
+
+            fn into_owned(self) -> Self::Owned {
+                self
+            }
+        }
+    )*};
+}
+
+owned_values!(
+    u8,
+    u16,
+    u32,
+    u64,
+    u128,
+    usize,
+    i8,
+    i16,
+    i32,
+    i64,
+    i128,
+    isize,
+    f32,
+    f64,
+    bool,
+    char,
+    (),
+    String,
+);
+
+impl<T: ?Sized + 'static> IntoOwned for PhantomData<T> {
+    type Owned = PhantomData<T>;
+
+    fn into_owned(self) -> Self::Owned {
+        self
+    }
+}
+
+macro_rules! owned_tuples {
+    ( $( ( $( $t:ident $idx:tt ),+ ) )* ) => {$(
+        impl<$( $t: IntoOwned ),+> IntoOwned for ( $( $t, )+ ) {
+            type Owned = ( $( $t::Owned, )+ );
+
+            fn into_owned(self) -> Self::Owned {
+                ( $( self.$idx.into_owned(), )+ )
+            }
+        }
+    )*};
+}
+
+owned_tuples! {
+    (A 0)
+    (A 0, B 1)
+    (A 0, B 1, C 2)
+    (A 0, B 1, C 2, D 3)
+}
+
+/* ------------------------------------------------------------------------- */
+/* Owned change enums                                                        */
+/* ------------------------------------------------------------------------- */
+
+/// Owned [`Changed`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChangedOwned<T, C = ()> {
+    Added(T),
+    Removed(T),
+    AddedAt(usize, T, usize),
+    RemovedAt(usize, T, usize),
+    Moved(T, usize, usize),
+    AddedRange(usize, Vec<T>),
+    RemovedRange(usize, Vec<T>),
+    ModifiedAt(usize, T, Vec<C>),
+}
+
+impl<T: Clone + Debug + 'static, C: IntoOwned> IntoOwned for Changed<'_, T, C> {
+    type Owned = ChangedOwned<T, C::Owned>;
+
+    fn into_owned(self) -> Self::Owned {
+        match self {
+            Changed::Added(v) => ChangedOwned::Added(v.clone()),
+            Changed::Removed(v) => ChangedOwned::Removed(v.clone()),
+            Changed::AddedAt(i, v, nth) => ChangedOwned::AddedAt(i, v.clone(), nth),
+            Changed::RemovedAt(i, v, nth) => ChangedOwned::RemovedAt(i, v.clone(), nth),
+            Changed::Moved(v, from, to) => ChangedOwned::Moved(v.clone(), from, to),
+            Changed::AddedRange(i, vs) => ChangedOwned::AddedRange(i, vs.to_vec()),
+            Changed::RemovedRange(i, vs) => ChangedOwned::RemovedRange(i, vs.to_vec()),
+            Changed::ModifiedAt(i, v, subs) => {
+                ChangedOwned::ModifiedAt(i, v.clone(), subs.into_owned())
+            }
+        }
+    }
+}
+
+/// Owned [`MapChanged`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MapChangedOwned<K, V, C = ()> {
+    AddedEntry(K, V),
+    RemovedEntry(K, V),
+    ChangedEntry(K, V, V),
+    ModifiedEntry(K, Vec<C>),
+}
+
+impl<K, V, C> IntoOwned for MapChanged<'_, K, V, C>
+where
+    K: Clone + Debug + 'static,
+    V: Clone + Debug + 'static,
+    C: IntoOwned,
+{
+    type Owned = MapChangedOwned<K, V, C::Owned>;
+
+    fn into_owned(self) -> Self::Owned {
+        match self {
+            MapChanged::AddedEntry(k, v) => MapChangedOwned::AddedEntry(k.clone(), v.clone()),
+            MapChanged::RemovedEntry(k, v) => MapChangedOwned::RemovedEntry(k.clone(), v.clone()),
+            MapChanged::ChangedEntry(k, ov, nv) => {
+                MapChangedOwned::ChangedEntry(k.clone(), ov.clone(), nv.clone())
+            }
+            MapChanged::ModifiedEntry(k, subs) => {
+                MapChangedOwned::ModifiedEntry(k.clone(), subs.into_owned())
+            }
+        }
+    }
+}
+
+/// Owned [`OptionChanged`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OptionChangedOwned<T, C = ()> {
+    Set(T),
+    Unset(T),
+    Inner(C),
+}
+
+impl<T: Clone + Debug + 'static, C: IntoOwned> IntoOwned for OptionChanged<'_, T, C> {
+    type Owned = OptionChangedOwned<T, C::Owned>;
+
+    fn into_owned(self) -> Self::Owned {
+        match self {
+            OptionChanged::Set(v) => OptionChangedOwned::Set(v.clone()),
+            OptionChanged::Unset(v) => OptionChangedOwned::Unset(v.clone()),
+            OptionChanged::Inner(c) => OptionChangedOwned::Inner(c.into_owned()),
+        }
+    }
+}
+
+/// Tuple changes own their data once every element change does.
+macro_rules! owned_tuple_changed {
+    ( $( $p:ident $item:ident ),* ) => {
+        impl<$( $p: IntoOwned ),*> IntoOwned for TupleChanged<$( $p ),*> {
+            type Owned = TupleChanged<$( $p::Owned ),*>;
+
+            fn into_owned(self) -> Self::Owned {
+                match self {
+                    $( TupleChanged::$item(c) => TupleChanged::$item(c.into_owned()), )*
+                }
+            }
+        }
+    };
+}
+
+owned_tuple_changed!(
+    P0 item0, P1 item1, P2 item2, P3 item3, P4 item4, P5 item5,
+    P6 item6, P7 item7, P8 item8, P9 item9, P10 item10, P11 item11
+);
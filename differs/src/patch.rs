@@ -0,0 +1,804 @@
+//! Replaying owned change sets onto a value (`#[differs(patch)]`).
+
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque},
+    fmt::{self, Debug, Display},
+    hash::{BuildHasher, Hash},
+    rc::Rc,
+    sync::Arc,
+};
+
+use crate::{ChangedOwned, HasChanges, MapChangedOwned, OptionChangedOwned, TupleChanged, Unused};
+
+/// Turns a value back into the `new` side of a diff: applying the owned
+/// form of `diff_changes(&old, &new)` to `old` leaves it equal to `new`.
+///
+/// Implemented by this crate for std scalars, smart pointers and containers,
+/// and by **`#[derive(Diff)]`** with `#[differs(patch)]`.
+pub trait ApplyChanges: HasChanges {
+    /// The owned change type, `<Self::Change<'_> as IntoOwned>::Owned`.
+    type Patch: Clone + Debug + 'static;
+
+    /// Fails when the value is not the one the patch was computed from; the
+    /// value may then be partly patched.
+    fn apply_changes<'p, I>(&mut self, patch: I) -> Result<(), PatchError>
+    where
+        I: IntoIterator<Item = &'p Self::Patch>;
+}
+
+/// Convenience helper.
+#[inline]
+pub fn apply_changes<T: ApplyChanges>(
+    target: &mut T,
+    patch: &[T::Patch],
+) -> Result<(), PatchError> {
+    target.apply_changes(patch)
+}
+
+/// A patch that does not fit its target, and where in the target it failed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PatchError {
+    path: Vec<String>,
+    kind: PatchErrorKind,
+}
+
+/// What did not fit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PatchErrorKind {
+    /// An `old_new` field, or a removed or moved element, differs from the
+    /// value the patch recorded.
+    Mismatch,
+    /// The element, entry, index or `Some` value the patch changes is not
+    /// there.
+    Missing,
+    /// The element or entry the patch adds is already there.
+    Present,
+    /// The enum holds another variant than the patch expects.
+    Variant,
+    /// The change cannot apply to this kind of value (e.g. `Moved` on a set).
+    Unsupported,
+}
+
+impl PatchError {
+    pub fn new(kind: PatchErrorKind) -> Self {
+        Self {
+            path: Vec::new(),
+            kind,
+        }
+    }
+
+    /// Prefixes the path with the field, index or key the error occurred in.
+    pub fn within(mut self, segment: impl Into<String>) -> Self {
+        self.path.insert(0, segment.into());
+        self
+    }
+
+    pub fn kind(&self) -> PatchErrorKind {
+        self.kind
+    }
+
+    /// Dotted path to the offending value (e.g. `"tags.2"`), empty at the
+    /// root.
+    pub fn path(&self) -> String {
+        self.path.join(".")
+    }
+}
+
+impl Display for PatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let what = match self.kind {
+            PatchErrorKind::Mismatch => "value differs from the patch's base",
+            PatchErrorKind::Missing => "value to change is missing",
+            PatchErrorKind::Present => "value to add is already present",
+            PatchErrorKind::Variant => "enum holds another variant",
+            PatchErrorKind::Unsupported => "change does not apply to this kind of value",
+        };
+        if self.path.is_empty() {
+            write!(f, "patch does not apply: {what}")
+        } else {
+            write!(f, "patch does not apply at `{}`: {what}", self.path())
+        }
+    }
+}
+
+impl std::error::Error for PatchError {}
+
+fn fail<T>(kind: PatchErrorKind) -> Result<T, PatchError> {
+    Err(PatchError::new(kind))
+}
+
+/* ------------------------------------------------------------------------- */
+/* Helpers shared with the derive                                            */
+/* ------------------------------------------------------------------------- */
+
+/// Functions `#[differs(patch)]` expands to, re-exported from `__private`.
+pub(crate) mod glue {
+    use super::*;
+
+    /// Applies the changes `pick` selects from `patch`, if any, with errors
+    /// prefixed by `segment`.
+    pub fn apply_part<'p, P, C>(
+        patch: &[&'p P],
+        pick: impl Fn(&'p P) -> Option<C>,
+        segment: &str,
+        apply: impl FnOnce(Vec<C>) -> Result<(), PatchError>,
+    ) -> Result<(), PatchError> {
+        let changes: Vec<C> = patch.iter().filter_map(|c| pick(c)).collect();
+        if changes.is_empty() {
+            return Ok(());
+        }
+        apply(changes).map_err(|e| e.within(segment))
+    }
+
+    /// Replaced value; the last change wins.
+    pub fn apply_value<T: From<V>, V: Clone>(
+        target: &mut T,
+        changes: Vec<&V>,
+    ) -> Result<(), PatchError> {
+        for v in changes {
+            *target = T::from(v.clone());
+        }
+        Ok(())
+    }
+
+    /// `old_new` value: the target must be `same` as the recorded old value.
+    pub fn apply_old_new<T: Clone>(
+        target: &mut T,
+        changes: Vec<(&T, &T)>,
+        same: impl Fn(&T, &T) -> bool,
+    ) -> Result<(), PatchError> {
+        for (old, new) in changes {
+            if !same(target, old) {
+                return fail(PatchErrorKind::Mismatch);
+            }
+            *target = new.clone();
+        }
+        Ok(())
+    }
+
+    pub fn apply_option<T: Clone, C>(
+        target: &mut Option<T>,
+        changes: Vec<&OptionChangedOwned<T, C>>,
+        nested: impl FnOnce(&mut T, Vec<&C>) -> Result<(), PatchError>,
+    ) -> Result<(), PatchError> {
+        let mut inner = Vec::new();
+        for change in changes {
+            match change {
+                OptionChangedOwned::Set(v) => *target = Some(v.clone()),
+                OptionChangedOwned::Unset(_) => {
+                    if target.take().is_none() {
+                        return fail(PatchErrorKind::Missing);
+                    }
+                }
+                OptionChangedOwned::Inner(c) => inner.push(c),
+            }
+        }
+        match target {
+            _ if inner.is_empty() => Ok(()),
+            Some(v) => nested(v, inner),
+            None => fail(PatchErrorKind::Missing),
+        }
+    }
+
+    /// Rebuilds a sequence: moved and added elements land at their new
+    /// index, the untouched rest fills the gaps in order, and `ModifiedAt`
+    /// overwrites its slot last. Removed and moved elements must be `same`
+    /// as the recorded ones.
+    pub fn patched_vec<'t, T: Clone + 't, C>(
+        old: impl IntoIterator<Item = &'t T>,
+        changes: Vec<&ChangedOwned<T, C>>,
+        same: impl Fn(&T, &T) -> bool,
+    ) -> Result<Vec<T>, PatchError> {
+        let old: Vec<&T> = old.into_iter().collect();
+        let mut consumed = vec![false; old.len()];
+        let mut take = |idx: usize, v: &T| {
+            match old.get(idx) {
+                Some(_) if consumed[idx] => fail(PatchErrorKind::Missing),
+                Some(o) if !same(o, v) => fail(PatchErrorKind::Mismatch),
+                Some(_) => {
+                    consumed[idx] = true;
+                    Ok(())
+                }
+                None => fail(PatchErrorKind::Missing),
+            }
+            .map_err(|e: PatchError| e.within(idx.to_string()))
+        };
+
+        let mut placed: BTreeMap<usize, &T> = BTreeMap::new();
+        let mut modified = Vec::new();
+        for change in changes {
+            match change {
+                ChangedOwned::AddedAt(idx, v, _) => {
+                    placed.insert(*idx, v);
+                }
+                ChangedOwned::AddedRange(idx, vs) => {
+                    placed.extend(vs.iter().enumerate().map(|(k, v)| (idx + k, v)));
+                }
+                ChangedOwned::RemovedAt(idx, v, _) => take(*idx, v)?,
+                ChangedOwned::RemovedRange(idx, vs) => {
+                    for (k, v) in vs.iter().enumerate() {
+                        take(idx + k, v)?;
+                    }
+                }
+                ChangedOwned::Moved(v, from, to) => {
+                    take(*from, v)?;
+                    placed.insert(*to, v);
+                }
+                ChangedOwned::ModifiedAt(idx, v, _) => modified.push((*idx, v)),
+                ChangedOwned::Added(_) | ChangedOwned::Removed(_) => {
+                    return fail(PatchErrorKind::Unsupported);
+                }
+            }
+        }
+
+        let mut rest = old
+            .iter()
+            .zip(&consumed)
+            .filter(|(_, consumed)| !**consumed)
+            .map(|(v, _)| *v);
+        let len = placed.len() + rest.clone().count();
+        if let Some((&idx, _)) = placed.range(len..).next() {
+            return Err(PatchError::new(PatchErrorKind::Missing).within(idx.to_string()));
+        }
+        let mut out: Vec<T> = (0..len)
+            .map(|idx| match placed.get(&idx) {
+                Some(v) => (*v).clone(),
+                None => rest.next().expect("counted above").clone(),
+            })
+            .collect();
+        for (idx, v) in modified {
+            match out.get_mut(idx) {
+                Some(slot) => *slot = v.clone(),
+                None => {
+                    return Err(PatchError::new(PatchErrorKind::Missing).within(idx.to_string()))
+                }
+            }
+        }
+        Ok(out)
+    }
+
+    /// Positional: `ModifiedAt` replaces its slot.
+    pub fn apply_array<T: Clone, C>(
+        target: &mut [T],
+        changes: Vec<&ChangedOwned<T, C>>,
+    ) -> Result<(), PatchError> {
+        for change in changes {
+            let ChangedOwned::ModifiedAt(idx, v, _) = change else {
+                return fail(PatchErrorKind::Unsupported);
+            };
+            match target.get_mut(*idx) {
+                Some(slot) => *slot = v.clone(),
+                None => {
+                    return Err(PatchError::new(PatchErrorKind::Missing).within(idx.to_string()))
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Sets a patch can insert into and remove from.
+    pub trait PatchSet {
+        type Item: Clone + Debug;
+
+        fn patch_insert(&mut self, v: Self::Item) -> bool;
+        fn patch_remove(&mut self, v: &Self::Item) -> bool;
+    }
+
+    pub fn apply_set<S: PatchSet, C>(
+        target: &mut S,
+        changes: Vec<&ChangedOwned<S::Item, C>>,
+    ) -> Result<(), PatchError> {
+        for change in changes {
+            let (ok, kind, v) = match change {
+                ChangedOwned::Added(v) => {
+                    (target.patch_insert(v.clone()), PatchErrorKind::Present, v)
+                }
+                ChangedOwned::Removed(v) => (target.patch_remove(v), PatchErrorKind::Missing, v),
+                _ => return fail(PatchErrorKind::Unsupported),
+            };
+            if !ok {
+                return Err(PatchError::new(kind).within(format!("{v:?}")));
+            }
+        }
+        Ok(())
+    }
+
+    /// Maps a patch can insert into, remove from and modify in place.
+    pub trait PatchMap {
+        type Key: Clone + Debug;
+        type Value: Clone;
+
+        fn patch_get_mut(&mut self, k: &Self::Key) -> Option<&mut Self::Value>;
+        fn patch_insert(&mut self, k: Self::Key, v: Self::Value) -> Option<Self::Value>;
+        fn patch_remove(&mut self, k: &Self::Key) -> Option<Self::Value>;
+    }
+
+    /// `nested` applies a `ModifiedEntry`'s changes to the entry's value.
+    pub fn apply_map<M: PatchMap, C>(
+        target: &mut M,
+        changes: Vec<&MapChangedOwned<M::Key, M::Value, C>>,
+        mut nested: impl FnMut(&mut M::Value, Vec<&C>) -> Result<(), PatchError>,
+    ) -> Result<(), PatchError> {
+        for change in changes {
+            let (k, applied) = match change {
+                MapChangedOwned::AddedEntry(k, v) => (
+                    k,
+                    match target.patch_get_mut(k) {
+                        Some(_) => fail(PatchErrorKind::Present),
+                        None => {
+                            target.patch_insert(k.clone(), v.clone());
+                            Ok(())
+                        }
+                    },
+                ),
+                MapChangedOwned::RemovedEntry(k, _) => (
+                    k,
+                    match target.patch_remove(k) {
+                        Some(_) => Ok(()),
+                        None => fail(PatchErrorKind::Missing),
+                    },
+                ),
+                MapChangedOwned::ChangedEntry(k, _, new) => (
+                    k,
+                    match target.patch_get_mut(k) {
+                        Some(v) => {
+                            *v = new.clone();
+                            Ok(())
+                        }
+                        None => fail(PatchErrorKind::Missing),
+                    },
+                ),
+                MapChangedOwned::ModifiedEntry(k, subs) => (
+                    k,
+                    match target.patch_get_mut(k) {
+                        Some(v) => nested(v, subs.iter().collect()),
+                        None => fail(PatchErrorKind::Missing),
+                    },
+                ),
+            };
+            applied.map_err(|e| e.within(format!("{k:?}")))?;
+        }
+        Ok(())
+    }
+
+    impl<T: Hash + Eq + Clone + Debug, S: BuildHasher> PatchSet for HashSet<T, S> {
+        type Item = T;
+
+        fn patch_insert(&mut self, v: T) -> bool {
+            self.insert(v)
+        }
+
+        fn patch_remove(&mut self, v: &T) -> bool {
+            self.remove(v)
+        }
+    }
+
+    impl<T: Ord + Clone + Debug> PatchSet for BTreeSet<T> {
+        type Item = T;
+
+        fn patch_insert(&mut self, v: T) -> bool {
+            self.insert(v)
+        }
+
+        fn patch_remove(&mut self, v: &T) -> bool {
+            self.remove(v)
+        }
+    }
+
+    #[cfg(feature = "indexmap")]
+    impl<T: Hash + Eq + Clone + Debug, S: BuildHasher> PatchSet for indexmap::IndexSet<T, S> {
+        type Item = T;
+
+        fn patch_insert(&mut self, v: T) -> bool {
+            self.insert(v)
+        }
+
+        /* keeps the order of the remaining elements */
+        fn patch_remove(&mut self, v: &T) -> bool {
+            self.shift_remove(v)
+        }
+    }
+
+    impl<K: Hash + Eq + Clone + Debug, V: Clone, S: BuildHasher> PatchMap for HashMap<K, V, S> {
+        type Key = K;
+        type Value = V;
+
+        fn patch_get_mut(&mut self, k: &K) -> Option<&mut V> {
+            self.get_mut(k)
+        }
+
+        fn patch_insert(&mut self, k: K, v: V) -> Option<V> {
+            self.insert(k, v)
+        }
+
+        fn patch_remove(&mut self, k: &K) -> Option<V> {
+            self.remove(k)
+        }
+    }
+
+    impl<K: Ord + Clone + Debug, V: Clone> PatchMap for BTreeMap<K, V> {
+        type Key = K;
+        type Value = V;
+
+        fn patch_get_mut(&mut self, k: &K) -> Option<&mut V> {
+            self.get_mut(k)
+        }
+
+        fn patch_insert(&mut self, k: K, v: V) -> Option<V> {
+            self.insert(k, v)
+        }
+
+        fn patch_remove(&mut self, k: &K) -> Option<V> {
+            self.remove(k)
+        }
+    }
+
+    #[cfg(feature = "indexmap")]
+    impl<K: Hash + Eq + Clone + Debug, V: Clone, S: BuildHasher> PatchMap
+        for indexmap::IndexMap<K, V, S>
+    {
+        type Key = K;
+        type Value = V;
+
+        fn patch_get_mut(&mut self, k: &K) -> Option<&mut V> {
+            self.get_mut(k)
+        }
+
+        fn patch_insert(&mut self, k: K, v: V) -> Option<V> {
+            self.insert(k, v)
+        }
+
+        fn patch_remove(&mut self, k: &K) -> Option<V> {
+            self.shift_remove(k)
+        }
+    }
+}
+
+use glue::*;
+
+/* ------------------------------------------------------------------------- */
+/* Scalars                                                                   */
+/* ------------------------------------------------------------------------- */
+
+/// Atomic values: the patch is the new value.
+macro_rules! scalar_patches {
+    ( $( $ty:ty ),* $(,)? ) => {$(
+        impl ApplyChanges for $ty {
+            type Patch = $ty;
+
+            fn apply_changes<'p, I>(&mut self, patch: I) -> Result<(), PatchError>
+            where
+                I: IntoIterator<Item = &'p Self::Patch>,
+            {
+                apply_value(self, patch.into_iter().collect())
+            }
+        }
+    )*};
+}
+
+scalar_patches!(
+    u8,
+    u16,
+    u32,
+    u64,
+    u128,
+    usize,
+    i8,
+    i16,
+    i32,
+    i64,
+    i128,
+    isize,
+    f32,
+    f64,
+    bool,
+    char,
+    (),
+    String,
+    std::num::NonZeroU8,
+    std::num::NonZeroU16,
+    std::num::NonZeroU32,
+    std::num::NonZeroU64,
+    std::num::NonZeroU128,
+    std::num::NonZeroUsize,
+    std::num::NonZeroI8,
+    std::num::NonZeroI16,
+    std::num::NonZeroI32,
+    std::num::NonZeroI64,
+    std::num::NonZeroI128,
+    std::num::NonZeroIsize,
+    std::time::Duration,
+    std::time::Instant,
+    std::time::SystemTime,
+    std::path::PathBuf,
+    std::ffi::OsString,
+    std::net::IpAddr,
+    std::net::Ipv4Addr,
+    std::net::Ipv6Addr,
+    std::net::SocketAddr,
+    std::net::SocketAddrV4,
+    std::net::SocketAddrV6,
+    std::cmp::Ordering,
+);
+
+/// Markers never change, so their patches are empty.
+impl<T: ?Sized> ApplyChanges for std::marker::PhantomData<T> {
+    type Patch = Unused;
+
+    fn apply_changes<'p, I>(&mut self, patch: I) -> Result<(), PatchError>
+    where
+        I: IntoIterator<Item = &'p Self::Patch>,
+    {
+        match patch.into_iter().next() {
+            Some(never) => match *never {},
+            None => Ok(()),
+        }
+    }
+}
+
+/* ------------------------------------------------------------------------- */
+/* Smart pointers: patch the pointee                                         */
+/* ------------------------------------------------------------------------- */
+
+impl<T: ApplyChanges + ?Sized> ApplyChanges for Box<T> {
+    type Patch = T::Patch;
+
+    fn apply_changes<'p, I>(&mut self, patch: I) -> Result<(), PatchError>
+    where
+        I: IntoIterator<Item = &'p Self::Patch>,
+    {
+        T::apply_changes(&mut **self, patch)
+    }
+}
+
+/// Shared pointees are cloned first (`make_mut`).
+macro_rules! shared_patches {
+    ( $( $ptr:ident ),* ) => {$(
+        impl<T: ApplyChanges + Clone> ApplyChanges for $ptr<T> {
+            type Patch = T::Patch;
+
+            fn apply_changes<'p, I>(&mut self, patch: I) -> Result<(), PatchError>
+            where
+                I: IntoIterator<Item = &'p Self::Patch>,
+            {
+                T::apply_changes($ptr::make_mut(self), patch)
+            }
+        }
+    )*};
+}
+
+shared_patches!(Rc, Arc);
+
+/// `str` pointees cannot be patched in place and are replaced whole.
+macro_rules! str_patches {
+    ( $( $ptr:ty ),* ) => {$(
+        impl ApplyChanges for $ptr {
+            type Patch = String;
+
+            fn apply_changes<'p, I>(&mut self, patch: I) -> Result<(), PatchError>
+            where
+                I: IntoIterator<Item = &'p Self::Patch>,
+            {
+                apply_value(self, patch.into_iter().collect())
+            }
+        }
+    )*};
+}
+
+str_patches!(Box<str>, Rc<str>, Arc<str>);
+
+/// Borrowed values are cloned first (`to_mut`).
+impl<B> ApplyChanges for Cow<'_, B>
+where
+    B: HasChanges + ToOwned + ?Sized,
+    B::Owned: ApplyChanges,
+{
+    type Patch = <B::Owned as ApplyChanges>::Patch;
+
+    fn apply_changes<'p, I>(&mut self, patch: I) -> Result<(), PatchError>
+    where
+        I: IntoIterator<Item = &'p Self::Patch>,
+    {
+        self.to_mut().apply_changes(patch)
+    }
+}
+
+/* ------------------------------------------------------------------------- */
+/* Containers                                                                */
+/* ------------------------------------------------------------------------- */
+
+impl<T: ApplyChanges + Clone + Debug + 'static> ApplyChanges for Option<T> {
+    type Patch = OptionChangedOwned<T, T::Patch>;
+
+    fn apply_changes<'p, I>(&mut self, patch: I) -> Result<(), PatchError>
+    where
+        I: IntoIterator<Item = &'p Self::Patch>,
+    {
+        apply_option(self, patch.into_iter().collect(), |v, subs| {
+            v.apply_changes(subs)
+        })
+    }
+}
+
+impl<T: ApplyChanges + Clone + PartialEq + Debug + 'static> ApplyChanges for Vec<T> {
+    type Patch = ChangedOwned<T, T::Patch>;
+
+    fn apply_changes<'p, I>(&mut self, patch: I) -> Result<(), PatchError>
+    where
+        I: IntoIterator<Item = &'p Self::Patch>,
+    {
+        *self = patched_vec(self.iter(), patch.into_iter().collect(), T::eq)?;
+        Ok(())
+    }
+}
+
+impl<T: ApplyChanges + Clone + PartialEq + Debug + 'static> ApplyChanges for VecDeque<T> {
+    type Patch = ChangedOwned<T, T::Patch>;
+
+    fn apply_changes<'p, I>(&mut self, patch: I) -> Result<(), PatchError>
+    where
+        I: IntoIterator<Item = &'p Self::Patch>,
+    {
+        *self = patched_vec(self.iter(), patch.into_iter().collect(), T::eq)?.into();
+        Ok(())
+    }
+}
+
+impl<T: ApplyChanges + Clone + Debug + 'static, const N: usize> ApplyChanges for [T; N] {
+    type Patch = ChangedOwned<T, T::Patch>;
+
+    fn apply_changes<'p, I>(&mut self, patch: I) -> Result<(), PatchError>
+    where
+        I: IntoIterator<Item = &'p Self::Patch>,
+    {
+        apply_array(self, patch.into_iter().collect())
+    }
+}
+
+impl<T, S> ApplyChanges for HashSet<T, S>
+where
+    T: Hash + Eq + Clone + Debug + 'static,
+    S: BuildHasher,
+{
+    type Patch = ChangedOwned<T>;
+
+    fn apply_changes<'p, I>(&mut self, patch: I) -> Result<(), PatchError>
+    where
+        I: IntoIterator<Item = &'p Self::Patch>,
+    {
+        apply_set(self, patch.into_iter().collect())
+    }
+}
+
+impl<T: Ord + Clone + Debug + 'static> ApplyChanges for BTreeSet<T> {
+    type Patch = ChangedOwned<T>;
+
+    fn apply_changes<'p, I>(&mut self, patch: I) -> Result<(), PatchError>
+    where
+        I: IntoIterator<Item = &'p Self::Patch>,
+    {
+        apply_set(self, patch.into_iter().collect())
+    }
+}
+
+impl<K, V, S> ApplyChanges for HashMap<K, V, S>
+where
+    K: Hash + Eq + Clone + Debug + 'static,
+    V: ApplyChanges + Clone + Debug + 'static,
+    S: BuildHasher,
+{
+    type Patch = MapChangedOwned<K, V, V::Patch>;
+
+    fn apply_changes<'p, I>(&mut self, patch: I) -> Result<(), PatchError>
+    where
+        I: IntoIterator<Item = &'p Self::Patch>,
+    {
+        apply_map(self, patch.into_iter().collect(), |v, subs| {
+            v.apply_changes(subs)
+        })
+    }
+}
+
+impl<K, V> ApplyChanges for BTreeMap<K, V>
+where
+    K: Ord + Clone + Debug + 'static,
+    V: ApplyChanges + Clone + Debug + 'static,
+{
+    type Patch = MapChangedOwned<K, V, V::Patch>;
+
+    fn apply_changes<'p, I>(&mut self, patch: I) -> Result<(), PatchError>
+    where
+        I: IntoIterator<Item = &'p Self::Patch>,
+    {
+        apply_map(self, patch.into_iter().collect(), |v, subs| {
+            v.apply_changes(subs)
+        })
+    }
+}
+
+#[cfg(feature = "indexmap")]
+impl<T, S> ApplyChanges for indexmap::IndexSet<T, S>
+where
+    T: Hash + Eq + Clone + Debug + 'static,
+    S: BuildHasher,
+{
+    type Patch = ChangedOwned<T>;
+
+    fn apply_changes<'p, I>(&mut self, patch: I) -> Result<(), PatchError>
+    where
+        I: IntoIterator<Item = &'p Self::Patch>,
+    {
+        apply_set(self, patch.into_iter().collect())
+    }
+}
+
+#[cfg(feature = "indexmap")]
+impl<K, V, S> ApplyChanges for indexmap::IndexMap<K, V, S>
+where
+    K: Hash + Eq + Clone + Debug + 'static,
+    V: ApplyChanges + Clone + Debug + 'static,
+    S: BuildHasher,
+{
+    type Patch = MapChangedOwned<K, V, V::Patch>;
+
+    fn apply_changes<'p, I>(&mut self, patch: I) -> Result<(), PatchError>
+    where
+        I: IntoIterator<Item = &'p Self::Patch>,
+    {
+        apply_map(self, patch.into_iter().collect(), |v, subs| {
+            v.apply_changes(subs)
+        })
+    }
+}
+
+/* ------------------------------------------------------------------------- */
+/* Tuples: element-wise                                                      */
+/* ------------------------------------------------------------------------- */
+
+macro_rules! tuple_patches {
+    ( $( ( $( $t:ident $idx:tt $item:ident ),+ ) )* ) => {$(
+        impl<$( $t: ApplyChanges ),+> ApplyChanges for ( $( $t, )+ ) {
+            type Patch = TupleChanged<$( $t::Patch ),+>;
+
+            /* `I` already names the ninth element */
+            fn apply_changes<'p, Ps>(&mut self, patch: Ps) -> Result<(), PatchError>
+            where
+                Ps: IntoIterator<Item = &'p Self::Patch>,
+            {
+                let patch: Vec<&Self::Patch> = patch.into_iter().collect();
+                $(
+                    apply_part(
+                        &patch,
+                        |c| match c {
+                            TupleChanged::$item(c) => Some(c),
+                            _ => None,
+                        },
+                        stringify!($idx),
+                        |changes| self.$idx.apply_changes(changes),
+                    )?;
+                )+
+                Ok(())
+            }
+        }
+    )*};
+}
+
+tuple_patches! {
+    (A 0 item0)
+    (A 0 item0, B 1 item1)
+    (A 0 item0, B 1 item1, C 2 item2)
+    (A 0 item0, B 1 item1, C 2 item2, D 3 item3)
+    (A 0 item0, B 1 item1, C 2 item2, D 3 item3, E 4 item4)
+    (A 0 item0, B 1 item1, C 2 item2, D 3 item3, E 4 item4, F 5 item5)
+    (A 0 item0, B 1 item1, C 2 item2, D 3 item3, E 4 item4, F 5 item5, G 6 item6)
+    (A 0 item0, B 1 item1, C 2 item2, D 3 item3, E 4 item4, F 5 item5, G 6 item6, H 7 item7)
+    (A 0 item0, B 1 item1, C 2 item2, D 3 item3, E 4 item4, F 5 item5, G 6 item6, H 7 item7,
+     I 8 item8)
+    (A 0 item0, B 1 item1, C 2 item2, D 3 item3, E 4 item4, F 5 item5, G 6 item6, H 7 item7,
+     I 8 item8, J 9 item9)
+    (A 0 item0, B 1 item1, C 2 item2, D 3 item3, E 4 item4, F 5 item5, G 6 item6, H 7 item7,
+     I 8 item8, J 9 item9, K 10 item10)
+    (A 0 item0, B 1 item1, C 2 item2, D 3 item3, E 4 item4, F 5 item5, G 6 item6, H 7 item7,
+     I 8 item8, J 9 item9, K 10 item10, L 11 item11)
+}
@@ -0,0 +1,468 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    ops::{Index, IndexMut, Range},
+};
+
+use crate::Changed;
+
+/// Longest common subsequence of `old` and `new` (Myers' O(ND) algorithm, in
+/// its linear-space divide-and-conquer form), as ascending
+/// `(old_idx, new_idx)` pairs.
+pub fn myers_lcs<T: PartialEq>(old: &[T], new: &[T]) -> Vec<(usize, usize)> {
+    let max_d = (old.len() + new.len()).div_ceil(2) + 1;
+    let mut forward = Diagonals::new(max_d);
+    let mut backward = Diagonals::new(max_d);
+    let mut pairs = Vec::new();
+    conquer(
+        old,
+        0..old.len(),
+        new,
+        0..new.len(),
+        &mut forward,
+        &mut backward,
+        &mut pairs,
+    );
+    pairs
+}
+
+/// Furthest `x` reached on every diagonal `k = x - y`, for `|k| <= max_d`.
+struct Diagonals {
+    v: Vec<usize>,
+    offset: isize,
+}
+
+impl Diagonals {
+    fn new(max_d: usize) -> Self {
+        Diagonals {
+            v: vec![0; 2 * max_d + 1],
+            offset: max_d as isize,
+        }
+    }
+}
+
+impl Index<isize> for Diagonals {
+    type Output = usize;
+
+    fn index(&self, k: isize) -> &usize {
+        &self.v[(k + self.offset) as usize]
+    }
+}
+
+impl IndexMut<isize> for Diagonals {
+    fn index_mut(&mut self, k: isize) -> &mut usize {
+        &mut self.v[(k + self.offset) as usize]
+    }
+}
+
+/// Appends the LCS of `old[o]` and `new[n]`: strips the common prefix and
+/// suffix, then splits the rest at a middle snake.
+fn conquer<T: PartialEq>(
+    old: &[T],
+    mut o: Range<usize>,
+    new: &[T],
+    mut n: Range<usize>,
+    forward: &mut Diagonals,
+    backward: &mut Diagonals,
+    pairs: &mut Vec<(usize, usize)>,
+) {
+    while o.start < o.end && n.start < n.end && old[o.start] == new[n.start] {
+        pairs.push((o.start, n.start));
+        o.start += 1;
+        n.start += 1;
+    }
+    let mut suffix = 0;
+    while o.start < o.end - suffix
+        && n.start < n.end - suffix
+        && old[o.end - suffix - 1] == new[n.end - suffix - 1]
+    {
+        suffix += 1;
+    }
+    o.end -= suffix;
+    n.end -= suffix;
+
+    if !o.is_empty() && !n.is_empty() {
+        let (x, y) = middle_snake(old, o.clone(), new, n.clone(), forward, backward);
+        conquer(old, o.start..x, new, n.start..y, forward, backward, pairs);
+        conquer(old, x..o.end, new, y..n.end, forward, backward, pairs);
+    }
+    pairs.extend((0..suffix).map(|i| (o.end + i, n.end + i)));
+}
+
+/// Start of a snake on an optimal path through `old[o]` / `new[n]` (both
+/// non-empty), found by searching from both ends until the paths overlap.
+/// Only the current diagonals are kept, so memory stays O(N + M).
+fn middle_snake<T: PartialEq>(
+    old: &[T],
+    o: Range<usize>,
+    new: &[T],
+    n: Range<usize>,
+    forward: &mut Diagonals,
+    backward: &mut Diagonals,
+) -> (usize, usize) {
+    let (len_o, len_n) = (o.len(), n.len());
+    let delta = len_o as isize - len_n as isize;
+    let odd = delta & 1 == 1;
+    forward[1] = 0;
+    backward[1] = 0;
+    let down = |v: &Diagonals, k: isize, d: isize| k == -d || (k != d && v[k - 1] < v[k + 1]);
+
+    for d in 0..=(len_o + len_n).div_ceil(2) as isize {
+        for k in (-d..=d).rev().step_by(2) {
+            let mut x = if down(forward, k, d) {
+                forward[k + 1]
+            } else {
+                forward[k - 1] + 1
+            };
+            let y = (x as isize - k) as usize;
+            let start = (x, y);
+            let mut y = y;
+            while x < len_o && y < len_n && old[o.start + x] == new[n.start + y] {
+                x += 1;
+                y += 1;
+            }
+            forward[k] = x;
+            if odd && (k - delta).abs() < d && x + backward[delta - k] >= len_o {
+                return (o.start + start.0, n.start + start.1);
+            }
+        }
+        /* backward: `x` / `y` count from the ends */
+        for k in (-d..=d).rev().step_by(2) {
+            let mut x = if down(backward, k, d) {
+                backward[k + 1]
+            } else {
+                backward[k - 1] + 1
+            };
+            let mut y = (x as isize - k) as usize;
+            while x < len_o && y < len_n && old[o.end - x - 1] == new[n.end - y - 1] {
+                x += 1;
+                y += 1;
+            }
+            backward[k] = x;
+            if !odd && (k - delta).abs() <= d && x + forward[delta - k] >= len_o {
+                return (o.end - x, n.end - y);
+            }
+        }
+    }
+    unreachable!("the forward and backward paths meet by d = (N + M) / 2")
+}
+
+/// A group id for every element of `old` and `new`, shared by equal
+/// elements, and the number of groups. With `hash` (which must agree with
+/// `PartialEq`) elements are bucketed by it; without, each is compared with
+/// the first element of every group so far.
+fn group_ids<'x, T: PartialEq + ?Sized>(
+    old: &[&'x T],
+    new: &[&'x T],
+    hash: Option<fn(&T) -> u64>,
+) -> (Vec<usize>, Vec<usize>, usize) {
+    let mut firsts: Vec<&'x T> = Vec::new();
+    let mut buckets: HashMap<u64, Vec<usize>> = HashMap::new();
+    let mut id_of = |v: &'x T| {
+        let bucket = hash.map(|h| buckets.entry(h(v)).or_default());
+        let found = match &bucket {
+            Some(bucket) => bucket.iter().copied().find(|&g| firsts[g] == v),
+            None => (0..firsts.len()).find(|&g| firsts[g] == v),
+        };
+        found.unwrap_or_else(|| {
+            firsts.push(v);
+            if let Some(bucket) = bucket {
+                bucket.push(firsts.len() - 1);
+            }
+            firsts.len() - 1
+        })
+    };
+    let old_ids = old.iter().map(|v| id_of(v)).collect();
+    let new_ids = new.iter().map(|v| id_of(v)).collect();
+    (old_ids, new_ids, firsts.len())
+}
+
+/// Patience diff: anchors on values that occur exactly once on both sides,
+/// recursing between anchors and falling back to [`myers_lcs`] where there
+/// are none. `hash`, if the element type has one agreeing with `PartialEq`
+/// (see [`HasChanges::EQ_HASH`](crate::HasChanges::EQ_HASH)), groups equal
+/// values in linear time; without it they are found by comparison.
+pub fn patience_lcs<T: PartialEq>(
+    old: &[T],
+    new: &[T],
+    hash: Option<fn(&T) -> u64>,
+) -> Vec<(usize, usize)> {
+    let old: Vec<&T> = old.iter().collect();
+    let new: Vec<&T> = new.iter().collect();
+    let (old, new, _) = group_ids(&old, &new, hash);
+    let mut out = Vec::new();
+    patience_rec(&old, &new, 0, 0, &mut out);
+    out
+}
+
+/// [`patience_lcs`] over group ids.
+fn patience_rec(
+    old: &[usize],
+    new: &[usize],
+    old_at: usize,
+    new_at: usize,
+    out: &mut Vec<(usize, usize)>,
+) {
+    /* common prefix / suffix */
+    let pre = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    out.extend((0..pre).map(|i| (old_at + i, new_at + i)));
+    let (old, new) = (&old[pre..], &new[pre..]);
+    let suf = old
+        .iter()
+        .rev()
+        .zip(new.iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let (old_mid, new_mid) = (&old[..old.len() - suf], &new[..new.len() - suf]);
+    let (old_at, new_at) = (old_at + pre, new_at + pre);
+
+    /* values unique on both sides, in old order: (count in old, count in
+     * new, position in new) per value */
+    let mut counts: HashMap<usize, (usize, usize, usize)> = HashMap::new();
+    for &g in old_mid {
+        counts.entry(g).or_default().0 += 1;
+    }
+    for (j, g) in new_mid.iter().enumerate() {
+        if let Some(count) = counts.get_mut(g) {
+            count.1 += 1;
+            count.2 = j;
+        }
+    }
+    let anchors: Vec<(usize, usize)> = old_mid
+        .iter()
+        .enumerate()
+        .filter_map(|(i, g)| match counts[g] {
+            (1, 1, j) => Some((i, j)),
+            _ => None,
+        })
+        .collect();
+    let anchors = longest_increasing(&anchors);
+
+    if anchors.is_empty() {
+        out.extend(
+            myers_lcs(old_mid, new_mid)
+                .into_iter()
+                .map(|(i, j)| (old_at + i, new_at + j)),
+        );
+    } else {
+        let (mut oi, mut ni) = (0, 0);
+        for (i, j) in anchors {
+            patience_rec(
+                &old_mid[oi..i],
+                &new_mid[ni..j],
+                old_at + oi,
+                new_at + ni,
+                out,
+            );
+            out.push((old_at + i, new_at + j));
+            (oi, ni) = (i + 1, j + 1);
+        }
+        patience_rec(
+            &old_mid[oi..],
+            &new_mid[ni..],
+            old_at + oi,
+            new_at + ni,
+            out,
+        );
+    }
+
+    let (old_end, new_end) = (old_at + old_mid.len(), new_at + new_mid.len());
+    out.extend((0..suf).map(|i| (old_end + i, new_end + i)));
+}
+
+/// Longest run of `pairs` (sorted by `.0`) whose `.1` is strictly increasing.
+fn longest_increasing(pairs: &[(usize, usize)]) -> Vec<(usize, usize)> {
+    /* tails[len] = index into `pairs` ending the best run of length len+1 */
+    let mut tails: Vec<usize> = Vec::new();
+    let mut prev = vec![None; pairs.len()];
+    for (idx, &(_, j)) in pairs.iter().enumerate() {
+        let pos = tails.partition_point(|&t| pairs[t].1 < j);
+        if pos > 0 {
+            prev[idx] = Some(tails[pos - 1]);
+        }
+        if pos == tails.len() {
+            tails.push(idx);
+        } else {
+            tails[pos] = idx;
+        }
+    }
+
+    let mut run = Vec::new();
+    let mut cur = tails.last().copied();
+    while let Some(idx) = cur {
+        run.push(pairs[idx]);
+        cur = prev[idx];
+    }
+    run.reverse();
+    run
+}
+
+/// Turn an LCS (`matched`, ascending pairs) into change events.
+///
+/// Unmatched elements that are equal on both sides become `Moved`; the rest
+/// are `AddedAt` / `RemovedAt`. With `nested`, unmatched elements left at the
+/// same index on both sides (in line with the LCS) are paired up as
+/// `ModifiedAt`, and kept elements whose nested diff is non-empty report
+/// `ModifiedAt` as well. `hash` groups equal elements as in
+/// [`patience_lcs`].
+pub fn ordered_changes<'a, T: PartialEq, C>(
+    old: &'a [T],
+    new: &'a [T],
+    matched: &[(usize, usize)],
+    nested: Option<fn(&'a T, &'a T) -> Vec<C>>,
+    hash: Option<fn(&T) -> u64>,
+) -> Vec<Changed<'a, T, C>> {
+    let old: Vec<&T> = old.iter().collect();
+    let new: Vec<&T> = new.iter().collect();
+    ordered_ref_changes(&old, &new, matched, nested, hash)
+}
+
+/// [`ordered_changes`] over element references, for non-contiguous sources.
+pub(crate) fn ordered_ref_changes<'a, T: PartialEq, C>(
+    old: &[&'a T],
+    new: &[&'a T],
+    matched: &[(usize, usize)],
+    nested: Option<fn(&'a T, &'a T) -> Vec<C>>,
+    hash: Option<fn(&T) -> u64>,
+) -> Vec<Changed<'a, T, C>> {
+    let (old_ids, new_ids, groups) = group_ids(old, new, hash);
+
+    /* new index -> old index it was matched to */
+    let mut source: Vec<Option<usize>> = vec![None; new.len()];
+    let mut old_free = vec![true; old.len()];
+    for &(i, j) in matched {
+        source[j] = Some(i);
+        old_free[i] = false;
+    }
+
+    /* genuine moves: an equal value left the common subsequence; unmatched
+     * old indices queue up by value, in order */
+    let mut unmatched = vec![VecDeque::new(); groups];
+    for (i, &g) in old_ids.iter().enumerate() {
+        if old_free[i] {
+            unmatched[g].push_back(i);
+        }
+    }
+    let mut moved = vec![false; new.len()];
+    for (j, &g) in new_ids.iter().enumerate() {
+        if source[j].is_none() {
+            if let Some(i) = unmatched[g].pop_front() {
+                source[j] = Some(i);
+                old_free[i] = false;
+                moved[j] = true;
+            }
+        }
+    }
+
+    /* `nth`: occurrences of each value so far */
+    let mut seen = vec![0; groups];
+    let mut out = Vec::new();
+    for (new_idx, &val) in new.iter().enumerate() {
+        let nth = seen[new_ids[new_idx]];
+        seen[new_ids[new_idx]] += 1;
+        let old_idx = match source[new_idx] {
+            Some(old_idx) => {
+                if moved[new_idx] {
+                    out.push(Changed::Moved(val, old_idx, new_idx));
+                }
+                old_idx
+            }
+            /* unmatched on both sides at the same index, without crossing
+             * the common subsequence (a patch could not tell it apart) */
+            None if nested.is_some()
+                && old_free.get(new_idx) == Some(&true)
+                && !crosses(matched, new_idx) =>
+            {
+                old_free[new_idx] = false;
+                new_idx
+            }
+            None => {
+                out.push(Changed::AddedAt(new_idx, val, nth));
+                continue;
+            }
+        };
+        if let Some(diff) = nested {
+            let subs = diff(old[old_idx], val);
+            if !subs.is_empty() {
+                out.push(Changed::ModifiedAt(new_idx, val, subs));
+            }
+        }
+    }
+
+    seen.fill(0);
+    for (old_idx, &val) in old.iter().enumerate() {
+        let nth = seen[old_ids[old_idx]];
+        seen[old_ids[old_idx]] += 1;
+        if old_free[old_idx] {
+            out.push(Changed::RemovedAt(old_idx, val, nth));
+        }
+    }
+    out
+}
+
+/// Would pairing old and new index `idx` cross a matched pair?
+fn crosses(matched: &[(usize, usize)], idx: usize) -> bool {
+    matched.partition_point(|&(i, _)| i < idx) != matched.partition_point(|&(_, j)| j < idx)
+}
+
+/// Pending run of `AddedAt` / `RemovedAt` events in [`coalesce_ranges`].
+struct Run<'a, T, C> {
+    added: bool,
+    start: usize,
+    pending: Vec<Changed<'a, T, C>>,
+}
+
+/// Collapse runs of adjacent `AddedAt` (or `RemovedAt`) events with
+/// consecutive indices into a single `AddedRange` (or `RemovedRange`).
+/// Lone events are kept as they are.
+pub fn coalesce_ranges<'a, T, C>(
+    old: &'a [T],
+    new: &'a [T],
+    changes: Vec<Changed<'a, T, C>>,
+) -> Vec<Changed<'a, T, C>> {
+    let mut out = Vec::with_capacity(changes.len());
+    let mut run: Option<Run<'a, T, C>> = None;
+
+    let flush = |run: Option<Run<'a, T, C>>, out: &mut Vec<Changed<'a, T, C>>| match run {
+        Some(mut run) if run.pending.len() == 1 => out.append(&mut run.pending),
+        Some(Run {
+            added,
+            start,
+            pending,
+        }) => {
+            let end = start + pending.len();
+            out.push(if added {
+                Changed::AddedRange(start, &new[start..end])
+            } else {
+                Changed::RemovedRange(start, &old[start..end])
+            });
+        }
+        None => {}
+    };
+
+    for change in changes {
+        let (added, idx) = match change {
+            Changed::AddedAt(idx, ..) => (true, idx),
+            Changed::RemovedAt(idx, ..) => (false, idx),
+            other => {
+                flush(run.take(), &mut out);
+                out.push(other);
+                continue;
+            }
+        };
+        match &mut run {
+            Some(r) if r.added == added && r.start + r.pending.len() == idx => {
+                r.pending.push(change)
+            }
+            _ => {
+                flush(run.take(), &mut out);
+                run = Some(Run {
+                    added,
+                    start: idx,
+                    pending: vec![change],
+                });
+            }
+        }
+    }
+    flush(run, &mut out);
+    out
+}
@@ -0,0 +1,250 @@
+//! Schemaless diffing of `serde_json::Value`, in the vocabulary of the
+//! derive: objects report [`MapChanged`] per member, arrays [`Changed`] per
+//! element (Myers, like `Vec`), anything else its new value.
+
+use serde_json::Value;
+
+use crate::{
+    change_path::nested_path, diff_changes, myers_lcs, ordered_changes, ChangePath, Changed,
+    ChangedOwned, FieldName, HasChanges, IntoOwned, MapChanged, MapChangedOwned,
+};
+
+/// Change of a `serde_json::Value`.
+///
+/// With the `serde` feature, serialized as `{"replaced": v}`,
+/// `{"object": change}` or `{"array": change}`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum ValueChange<'a> {
+    /// A different scalar, or a different JSON type; carries the new value.
+    Replaced(&'a Value),
+    /// One member change of an object on both sides. Members that are
+    /// objects (or arrays) on both sides are `ModifiedEntry`, any other
+    /// differing member a `ChangedEntry`.
+    Object(MapChanged<'a, String, Value, ValueChange<'a>>),
+    /// One element change of an array on both sides. A replaced scalar
+    /// element is a `ModifiedAt` without nested changes.
+    Array(Changed<'a, Value, ValueChange<'a>>),
+}
+
+/// Both objects, or both arrays: diffed member by member / element by
+/// element rather than replaced.
+fn same_shape(old: &Value, new: &Value) -> bool {
+    matches!(
+        (old, new),
+        (Value::Object(_), Value::Object(_)) | (Value::Array(_), Value::Array(_))
+    )
+}
+
+impl HasChanges for Value {
+    type Change<'a> = ValueChange<'a>;
+    const EQ_HASH: Option<fn(&Self) -> u64> = Some(crate::impls::eq_hash::<Value>);
+
+    fn collect_changes<'a>(old: &'a Self, new: &'a Self, out: &mut Vec<Self::Change<'a>>)
+    where
+        Self: 'a,
+    {
+        match (old, new) {
+            (Value::Object(old), Value::Object(new)) => {
+                for (k, ov) in old {
+                    out.push(ValueChange::Object(match new.get(k) {
+                        None => MapChanged::RemovedEntry(k, ov),
+                        Some(nv) if same_shape(ov, nv) => {
+                            let subs = diff_changes(ov, nv);
+                            if subs.is_empty() {
+                                continue;
+                            }
+                            MapChanged::ModifiedEntry(k, subs)
+                        }
+                        Some(nv) if ov != nv => MapChanged::ChangedEntry(k, ov, nv),
+                        Some(_) => continue,
+                    }));
+                }
+                for (k, nv) in new {
+                    if !old.contains_key(k) {
+                        out.push(ValueChange::Object(MapChanged::AddedEntry(k, nv)));
+                    }
+                }
+            }
+            (Value::Array(old), Value::Array(new)) => {
+                let matched = myers_lcs(old, new);
+                let nested = Some(diff_changes::<Value> as fn(_, _) -> _);
+                for change in ordered_changes(old, new, &matched, nested, Self::EQ_HASH) {
+                    out.push(ValueChange::Array(match change {
+                        /* replaced scalars carry no nested changes */
+                        Changed::ModifiedAt(idx, nv, subs)
+                            if matches!(subs[..], [ValueChange::Replaced(_)]) =>
+                        {
+                            Changed::ModifiedAt(idx, nv, Vec::new())
+                        }
+                        change => change,
+                    }));
+                }
+            }
+            _ if old != new => out.push(ValueChange::Replaced(new)),
+            _ => {}
+        }
+    }
+}
+
+/// The changes between `old` and `new`, each at the dotted path of the
+/// value it touches (`plugins[2].name`, `env["PATH"]`): nested
+/// `ModifiedEntry` / `ModifiedAt` changes are replaced by their leaves. Array
+/// elements are addressed by their new index, removed ones by their old.
+pub fn value_changes<'a>(old: &'a Value, new: &'a Value) -> Vec<(FieldName, ValueChange<'a>)> {
+    let mut out = Vec::new();
+    leaves(&FieldName::static_lit(""), diff_changes(old, new), &mut out);
+    out
+}
+
+/// Object members named like identifiers are fields (`a.b`), others keys
+/// (`a["b c"]`).
+fn member(at: &FieldName, key: &str) -> FieldName {
+    let ident = key.chars().next().is_some_and(|c| !c.is_ascii_digit())
+        && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if ident {
+        at.child(key)
+    } else {
+        at.key(key)
+    }
+}
+
+/// Object members follow [`value_changes`]: `a.b`, or `a["b c"]`.
+impl ChangePath for ValueChange<'_> {
+    fn path_below(&self, at: &FieldName) -> FieldName {
+        match self {
+            ValueChange::Replaced(_) => at.clone(),
+            ValueChange::Object(MapChanged::ModifiedEntry(k, subs)) => {
+                nested_path(member(at, k), subs)
+            }
+            ValueChange::Object(
+                MapChanged::AddedEntry(k, _)
+                | MapChanged::RemovedEntry(k, _)
+                | MapChanged::ChangedEntry(k, ..),
+            ) => member(at, k),
+            ValueChange::Array(c) => c.path_below(at),
+        }
+    }
+
+    fn vacated_below(&self, at: &FieldName, out: &mut Vec<FieldName>) {
+        match self {
+            ValueChange::Object(MapChanged::ModifiedEntry(k, subs)) => {
+                let at = member(at, k);
+                subs.iter().for_each(|c| c.vacated_below(&at, out));
+            }
+            ValueChange::Array(c) => c.vacated_below(at, out),
+            _ => {}
+        }
+    }
+}
+
+fn leaves<'a>(
+    at: &FieldName,
+    changes: Vec<ValueChange<'a>>,
+    out: &mut Vec<(FieldName, ValueChange<'a>)>,
+) {
+    for change in changes {
+        let path = match change {
+            ValueChange::Object(MapChanged::ModifiedEntry(k, subs)) => {
+                leaves(&member(at, k), subs, out);
+                continue;
+            }
+            ValueChange::Array(Changed::ModifiedAt(idx, _, subs)) if !subs.is_empty() => {
+                leaves(&at.index(idx), subs, out);
+                continue;
+            }
+            ValueChange::Replaced(_) => at.clone(),
+            ValueChange::Object(
+                MapChanged::AddedEntry(k, _)
+                | MapChanged::RemovedEntry(k, _)
+                | MapChanged::ChangedEntry(k, ..),
+            ) => member(at, k),
+            ValueChange::Array(
+                Changed::AddedAt(idx, ..)
+                | Changed::RemovedAt(idx, ..)
+                | Changed::Moved(_, _, idx)
+                | Changed::AddedRange(idx, _)
+                | Changed::RemovedRange(idx, _)
+                | Changed::ModifiedAt(idx, ..),
+            ) => at.index(idx),
+            /* sets only */
+            ValueChange::Array(Changed::Added(_) | Changed::Removed(_)) => at.clone(),
+        };
+        out.push((path, change));
+    }
+}
+
+/// [`ValueChange`] detached from the values it was computed from.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum ValueChangeOwned {
+    Replaced(Value),
+    Object(MapChangedOwned<String, Value, ValueChangeOwned>),
+    Array(ChangedOwned<Value, ValueChangeOwned>),
+}
+
+impl IntoOwned for ValueChange<'_> {
+    type Owned = ValueChangeOwned;
+
+    fn into_owned(self) -> Self::Owned {
+        match self {
+            ValueChange::Replaced(v) => ValueChangeOwned::Replaced(v.clone()),
+            ValueChange::Object(c) => ValueChangeOwned::Object(c.into_owned()),
+            ValueChange::Array(c) => ValueChangeOwned::Array(c.into_owned()),
+        }
+    }
+}
+
+/// [`value_changes`] between the `serde_json::Value` forms of any two
+/// serializable values, detached from those temporary forms.
+#[cfg(feature = "serde")]
+pub fn serialized_changes<T: serde::Serialize + ?Sized>(
+    old: &T,
+    new: &T,
+) -> Result<Vec<(FieldName, ValueChangeOwned)>, serde_json::Error> {
+    let old = serde_json::to_value(old)?;
+    let new = serde_json::to_value(new)?;
+    Ok(value_changes(&old, &new)
+        .into_iter()
+        .map(|(path, change)| (path, change.into_owned()))
+        .collect())
+}
+
+/// Objects write their members' operations, arrays their elements'.
+#[cfg(feature = "serde")]
+impl crate::JsonPatchOps for ValueChange<'_> {
+    fn json_patch_ops(
+        changes: &[&Self],
+        at: &str,
+        out: &mut Vec<Value>,
+    ) -> Result<(), serde_json::Error> {
+        use crate::json_patch::glue::{json_part, json_replace};
+
+        for change in changes {
+            if let ValueChange::Replaced(v) = change {
+                json_replace(*v, at, out)?;
+            }
+        }
+        let at = at.to_owned();
+        json_part(
+            changes,
+            |c| match c {
+                ValueChange::Object(c) => Some(c),
+                _ => None,
+            },
+            at.clone(),
+            out,
+        )?;
+        json_part(
+            changes,
+            |c| match c {
+                ValueChange::Array(c) => Some(c),
+                _ => None,
+            },
+            at,
+            out,
+        )
+    }
+}
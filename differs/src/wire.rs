@@ -0,0 +1,235 @@
+//! `serde` wire shapes of the library's change types (documented on each
+//! type). Borrowed and owned forms go through one representation per type,
+//! so they cannot drift apart.
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{
+    Changed, ChangedOwned, MapChanged, MapChangedOwned, OptionChanged, OptionChangedOwned,
+};
+
+/* ------------------------------------------------------------------------- */
+/* Changed                                                                   */
+/* ------------------------------------------------------------------------- */
+
+/// `V`: one element, `Vs`: a run of elements, `Cs`: nested changes.
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+enum ChangedRepr<V, Vs, Cs> {
+    Added { value: V },
+    Removed { value: V },
+    AddedAt { index: usize, value: V, nth: usize },
+    RemovedAt { index: usize, value: V, nth: usize },
+    Moved { value: V, from: usize, to: usize },
+    AddedRange { index: usize, values: Vs },
+    RemovedRange { index: usize, values: Vs },
+    ModifiedAt { index: usize, value: V, changes: Cs },
+}
+
+impl<T: Serialize, C: Serialize> Serialize for Changed<'_, T, C> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let repr: ChangedRepr<&T, &[T], &[C]> = match self {
+            Changed::Added(value) => ChangedRepr::Added { value },
+            Changed::Removed(value) => ChangedRepr::Removed { value },
+            &Changed::AddedAt(index, value, nth) => ChangedRepr::AddedAt { index, value, nth },
+            &Changed::RemovedAt(index, value, nth) => ChangedRepr::RemovedAt { index, value, nth },
+            &Changed::Moved(value, from, to) => ChangedRepr::Moved { value, from, to },
+            &Changed::AddedRange(index, values) => ChangedRepr::AddedRange { index, values },
+            &Changed::RemovedRange(index, values) => ChangedRepr::RemovedRange { index, values },
+            Changed::ModifiedAt(index, value, changes) => ChangedRepr::ModifiedAt {
+                index: *index,
+                value,
+                changes,
+            },
+        };
+        repr.serialize(serializer)
+    }
+}
+
+impl<T: Serialize, C: Serialize> Serialize for ChangedOwned<T, C> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let repr: ChangedRepr<&T, &[T], &[C]> = match self {
+            ChangedOwned::Added(value) => ChangedRepr::Added { value },
+            ChangedOwned::Removed(value) => ChangedRepr::Removed { value },
+            ChangedOwned::AddedAt(index, value, nth) => ChangedRepr::AddedAt {
+                index: *index,
+                value,
+                nth: *nth,
+            },
+            ChangedOwned::RemovedAt(index, value, nth) => ChangedRepr::RemovedAt {
+                index: *index,
+                value,
+                nth: *nth,
+            },
+            ChangedOwned::Moved(value, from, to) => ChangedRepr::Moved {
+                value,
+                from: *from,
+                to: *to,
+            },
+            ChangedOwned::AddedRange(index, values) => ChangedRepr::AddedRange {
+                index: *index,
+                values,
+            },
+            ChangedOwned::RemovedRange(index, values) => ChangedRepr::RemovedRange {
+                index: *index,
+                values,
+            },
+            ChangedOwned::ModifiedAt(index, value, changes) => ChangedRepr::ModifiedAt {
+                index: *index,
+                value,
+                changes,
+            },
+        };
+        repr.serialize(serializer)
+    }
+}
+
+impl<'de, T: Deserialize<'de>, C: Deserialize<'de>> Deserialize<'de> for ChangedOwned<T, C> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(
+            match ChangedRepr::<T, Vec<T>, Vec<C>>::deserialize(deserializer)? {
+                ChangedRepr::Added { value } => ChangedOwned::Added(value),
+                ChangedRepr::Removed { value } => ChangedOwned::Removed(value),
+                ChangedRepr::AddedAt { index, value, nth } => {
+                    ChangedOwned::AddedAt(index, value, nth)
+                }
+                ChangedRepr::RemovedAt { index, value, nth } => {
+                    ChangedOwned::RemovedAt(index, value, nth)
+                }
+                ChangedRepr::Moved { value, from, to } => ChangedOwned::Moved(value, from, to),
+                ChangedRepr::AddedRange { index, values } => {
+                    ChangedOwned::AddedRange(index, values)
+                }
+                ChangedRepr::RemovedRange { index, values } => {
+                    ChangedOwned::RemovedRange(index, values)
+                }
+                ChangedRepr::ModifiedAt {
+                    index,
+                    value,
+                    changes,
+                } => ChangedOwned::ModifiedAt(index, value, changes),
+            },
+        )
+    }
+}
+
+/* ------------------------------------------------------------------------- */
+/* MapChanged                                                                */
+/* ------------------------------------------------------------------------- */
+
+/// Variant names are the `"op"` tags, matching [`MapChanged`]'s.
+#[allow(clippy::enum_variant_names)]
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+enum MapChangedRepr<K, V, Cs> {
+    AddedEntry { key: K, value: V },
+    RemovedEntry { key: K, value: V },
+    ChangedEntry { key: K, old: V, new: V },
+    ModifiedEntry { key: K, changes: Cs },
+}
+
+impl<K: Serialize, V: Serialize, C: Serialize> Serialize for MapChanged<'_, K, V, C> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let repr: MapChangedRepr<&K, &V, &[C]> = match self {
+            MapChanged::AddedEntry(key, value) => MapChangedRepr::AddedEntry { key, value },
+            MapChanged::RemovedEntry(key, value) => MapChangedRepr::RemovedEntry { key, value },
+            MapChanged::ChangedEntry(key, old, new) => {
+                MapChangedRepr::ChangedEntry { key, old, new }
+            }
+            MapChanged::ModifiedEntry(key, changes) => {
+                MapChangedRepr::ModifiedEntry { key, changes }
+            }
+        };
+        repr.serialize(serializer)
+    }
+}
+
+impl<K: Serialize, V: Serialize, C: Serialize> Serialize for MapChangedOwned<K, V, C> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let repr: MapChangedRepr<&K, &V, &[C]> = match self {
+            MapChangedOwned::AddedEntry(key, value) => MapChangedRepr::AddedEntry { key, value },
+            MapChangedOwned::RemovedEntry(key, value) => {
+                MapChangedRepr::RemovedEntry { key, value }
+            }
+            MapChangedOwned::ChangedEntry(key, old, new) => {
+                MapChangedRepr::ChangedEntry { key, old, new }
+            }
+            MapChangedOwned::ModifiedEntry(key, changes) => {
+                MapChangedRepr::ModifiedEntry { key, changes }
+            }
+        };
+        repr.serialize(serializer)
+    }
+}
+
+impl<'de, K, V, C> Deserialize<'de> for MapChangedOwned<K, V, C>
+where
+    K: Deserialize<'de>,
+    V: Deserialize<'de>,
+    C: Deserialize<'de>,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(
+            match MapChangedRepr::<K, V, Vec<C>>::deserialize(deserializer)? {
+                MapChangedRepr::AddedEntry { key, value } => {
+                    MapChangedOwned::AddedEntry(key, value)
+                }
+                MapChangedRepr::RemovedEntry { key, value } => {
+                    MapChangedOwned::RemovedEntry(key, value)
+                }
+                MapChangedRepr::ChangedEntry { key, old, new } => {
+                    MapChangedOwned::ChangedEntry(key, old, new)
+                }
+                MapChangedRepr::ModifiedEntry { key, changes } => {
+                    MapChangedOwned::ModifiedEntry(key, changes)
+                }
+            },
+        )
+    }
+}
+
+/* ------------------------------------------------------------------------- */
+/* OptionChanged                                                             */
+/* ------------------------------------------------------------------------- */
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+enum OptionChangedRepr<V, C> {
+    Set { value: V },
+    Unset { value: V },
+    Inner { change: C },
+}
+
+impl<T: Serialize, C: Serialize> Serialize for OptionChanged<'_, T, C> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let repr: OptionChangedRepr<&T, &C> = match self {
+            OptionChanged::Set(value) => OptionChangedRepr::Set { value },
+            OptionChanged::Unset(value) => OptionChangedRepr::Unset { value },
+            OptionChanged::Inner(change) => OptionChangedRepr::Inner { change },
+        };
+        repr.serialize(serializer)
+    }
+}
+
+impl<T: Serialize, C: Serialize> Serialize for OptionChangedOwned<T, C> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let repr: OptionChangedRepr<&T, &C> = match self {
+            OptionChangedOwned::Set(value) => OptionChangedRepr::Set { value },
+            OptionChangedOwned::Unset(value) => OptionChangedRepr::Unset { value },
+            OptionChangedOwned::Inner(change) => OptionChangedRepr::Inner { change },
+        };
+        repr.serialize(serializer)
+    }
+}
+
+impl<'de, T: Deserialize<'de>, C: Deserialize<'de>> Deserialize<'de> for OptionChangedOwned<T, C> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(
+            match OptionChangedRepr::<T, C>::deserialize(deserializer)? {
+                OptionChangedRepr::Set { value } => OptionChangedOwned::Set(value),
+                OptionChangedRepr::Unset { value } => OptionChangedOwned::Unset(value),
+                OptionChangedRepr::Inner { change } => OptionChangedOwned::Inner(change),
+            },
+        )
+    }
+}
@@ -1,10 +1,12 @@
 use differs::{
-    changed, diff_changes, Changed,
-    Changed::{Added, AddedAt, Moved, Removed, RemovedAt},
-    Diff,
-    MapChanged::{AddedEntry, ChangedEntry, RemovedEntry},
+    changed, diff_changes, ApplyChanges, ChangePath, Changed,
+    Changed::{Added, AddedAt, ModifiedAt, Moved, Removed, RemovedAt},
+    ChangedOwned, Diff, IntoOwned,
+    MapChanged::{AddedEntry, ChangedEntry, ModifiedEntry, RemovedEntry},
+    MapChangedOwned, OptionChangedOwned, PatchErrorKind,
 };
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::marker::PhantomData;
 
 #[derive(Diff, Clone, Debug, PartialEq)]
 struct SimpleStruct {
@@ -63,6 +65,212 @@ struct Prefs {
     prefs: HashMap<&'static str, &'static str>,
 }
 
+#[derive(Diff, Clone, Debug, PartialEq)]
+struct UserId(u64);
+
+#[derive(Diff, Clone, Debug, PartialEq)]
+struct Tags(HashSet<String>);
+
+#[derive(Diff, Clone, Debug, PartialEq)]
+struct Labeled(String, #[differs(skip)] u32, UserId);
+
+#[derive(Diff, Clone, Debug, PartialEq)]
+struct Marker;
+
+#[derive(Diff, Clone, Debug, PartialEq)]
+struct Versioned<T> {
+    version: u64,
+    value: T,
+}
+
+#[derive(Diff, Clone, Debug, PartialEq)]
+struct Tagged<T> {
+    items: Vec<T>,
+}
+
+#[derive(Diff, Clone, Debug, PartialEq)]
+struct Borrowed<'a> {
+    name: &'a str,
+}
+
+#[derive(Diff, Clone, Debug, PartialEq)]
+struct Fixed<const N: usize> {
+    data: [u8; N],
+}
+
+#[derive(Diff, Clone, Debug, PartialEq)]
+#[differs(bound = "T: differs::HasChanges + PartialEq + std::fmt::Debug")]
+struct Bounded<T> {
+    inner: T,
+}
+
+#[derive(Diff, Clone, Debug, PartialEq)]
+enum Slot<T> {
+    Empty,
+    Full(T),
+}
+
+#[derive(Diff, Clone, Debug, PartialEq)]
+struct Session {
+    token: String,
+    peers: Vec<u32>,
+}
+
+#[derive(Diff, Clone, Debug, PartialEq)]
+enum ConnectionState {
+    Idle,
+    Connecting { attempt: u32 },
+    Connected(Session),
+}
+
+/* a variant named like a derived struct, and one shared by two enums */
+#[derive(Diff, Clone, Debug, PartialEq)]
+enum Message {
+    Config(Config),
+    Quit,
+}
+
+#[derive(Diff, Clone, Debug, PartialEq)]
+enum Lamp {
+    On { watts: u32 },
+}
+
+#[derive(Diff, Clone, Debug, PartialEq)]
+enum Fan {
+    On { speed: u8 },
+}
+
+#[derive(Diff, Clone, Debug, PartialEq)]
+#[differs(patch)]
+enum Token {
+    Word { r#type: u32, text: String },
+    End,
+}
+
+type Ids = Vec<u32>;
+
+#[derive(Diff, Clone, Debug, PartialEq)]
+struct Spellings {
+    direct: Vec<u32>,
+    aliased: Ids,
+    ports: Vec<Port>,
+}
+
+#[derive(Diff, Clone, Debug, PartialEq)]
+#[differs(patch)]
+struct Names {
+    boxed: Box<str>,
+    shared: std::rc::Rc<str>,
+    label: std::borrow::Cow<'static, str>,
+    count: std::sync::Arc<u32>,
+}
+
+#[derive(Diff, Clone, Debug, PartialEq)]
+struct Typed<T> {
+    id: u64,
+    _kind: PhantomData<T>,
+}
+
+#[derive(Diff, Clone, Debug, PartialEq)]
+#[differs(old_new)]
+struct Profile {
+    age: u32,
+    bio: String,
+}
+
+#[derive(Diff, Clone, Debug, PartialEq)]
+struct Account {
+    #[differs(old_new)]
+    username: String,
+    email: String,
+    profile: Profile,
+}
+
+#[derive(Diff, Clone, Debug, PartialEq, Eq, Hash)]
+struct Member {
+    id: u32,
+    name: String,
+}
+
+#[derive(Diff, Clone, Debug, PartialEq)]
+struct Team {
+    members: Vec<Member>,
+}
+
+#[derive(Diff, Clone, Debug, PartialEq)]
+struct Player {
+    id: u32,
+    score: u32,
+    badges: HashSet<String>,
+}
+
+fn player_id(p: &Player) -> u32 {
+    p.id
+}
+
+#[derive(Diff, Clone, Debug, PartialEq)]
+struct League {
+    #[differs(key = id)]
+    players: Vec<Player>,
+    #[differs(key_fn = player_id)]
+    reserves: Vec<Player>,
+}
+
+#[derive(Diff, Clone, Debug, PartialEq)]
+struct Playlist {
+    #[differs(algorithm = "myers")]
+    tracks: Vec<f32>,
+    #[differs(algorithm = "patience")]
+    players: Vec<Player>,
+}
+
+#[derive(Diff, Clone, Debug, PartialEq)]
+struct Log {
+    #[differs(ranges)]
+    lines: Vec<u32>,
+}
+
+#[derive(Diff, Clone, Debug, PartialEq)]
+struct Directory {
+    entries: HashMap<String, Leaf>,
+}
+
+#[derive(Diff, Clone, Debug, PartialEq)]
+struct Catalog {
+    prices: BTreeMap<String, u32>,
+    sections: BTreeSet<u32>,
+    queue: VecDeque<char>,
+}
+
+#[cfg(feature = "indexmap")]
+#[derive(Diff, Clone, Debug, PartialEq)]
+struct Indexed {
+    map: indexmap::IndexMap<&'static str, Leaf>,
+    set: indexmap::IndexSet<u32>,
+}
+
+#[derive(Diff, Clone, Debug, PartialEq)]
+struct Config {
+    mailing: Option<Address>,
+    nickname: Option<String>,
+}
+
+#[derive(Diff, Clone, Debug, PartialEq)]
+struct Shared {
+    root: Box<Leaf>,
+    #[differs(ptr_eq)]
+    settings: std::sync::Arc<Address>,
+    label: std::borrow::Cow<'static, str>,
+    counts: std::rc::Rc<Vec<u32>>,
+}
+
+#[derive(Diff, differs::Fields, Clone, Debug, PartialEq)]
+struct Geometry {
+    corners: [Leaf; 3],
+    origin: (f32, f32),
+    named: (String, Leaf, Vec<u8>),
+}
+
 #[test]
 fn test_no_changes() {
     let old = SimpleStruct {
@@ -98,7 +306,7 @@ fn test_scalar_changes() {
     for change in &changes {
         match change {
             SimpleStructChange::self_(changed) => {
-                assert_eq!(changed.name.as_ref(), "Jane");
+                assert_eq!(changed.name.as_str(), "Jane");
                 assert_eq!(*changed.age, 31);
                 assert!(changed.active);
                 found_self = true;
@@ -358,7 +566,11 @@ fn test_hashmap_changes() {
                     assert!(!found_added_timezone);
                     found_added_timezone = true;
                 }
-                ChangedEntry(key) if key.as_str() == "theme" => {
+                ChangedEntry(key, old, new)
+                    if key.as_str() == "theme"
+                        && old.as_str() == "dark"
+                        && new.as_str() == "light" =>
+                {
                     assert!(!found_changed_theme);
                     found_changed_theme = true;
                 }
@@ -396,8 +608,8 @@ fn test_skipped_fields() {
     for ch in changes {
         match &ch {
             WithSkippedFieldChange::self_(changed) => {
-                assert_eq!(changed.included.as_ref(), "new");
-                assert_eq!(changed.skipped.as_ref(), "new_skipped");
+                assert_eq!(changed.included.as_str(), "new");
+                assert_eq!(changed.skipped.as_str(), "new_skipped");
 
                 assert!(!found_self);
                 found_self = true;
@@ -492,9 +704,9 @@ fn test_changed_macro_with_snapshot() {
         changed!(change;
             Person.address@(snapshot) => {
                 snapshot_found = true;
-                assert_eq!(snapshot.street.as_ref(), "456 Oak Ave");
-                assert_eq!(snapshot.city.as_ref(), "Boston");
-                assert_eq!(snapshot.zip.as_ref(), "02101");
+                assert_eq!(snapshot.street.as_str(), "456 Oak Ave");
+                assert_eq!(snapshot.city.as_str(), "Boston");
+                assert_eq!(snapshot.zip.as_str(), "02101");
             };
         );
     }
@@ -694,3 +906,1598 @@ fn self_snapshot_emitted_when_anything_changed() {
     let diff = diff_changes(&old, &new);
     assert!(matches!(diff.first(), Some(LeafChange::self_(_))));
 }
+
+#[test]
+fn enum_same_variant_field_change() {
+    let old = ConnectionState::Connecting { attempt: 1 };
+    let new = ConnectionState::Connecting { attempt: 2 };
+
+    let diff = diff_changes(&old, &new);
+
+    assert!(matches!(
+        diff.as_slice(),
+        [
+            ConnectionStateChange::self_(ConnectionStateSnapshot::Connecting { attempt: &2 }),
+            ConnectionStateChange::Connecting(ConnectionStateConnectingChange::attempt(&2))
+        ]
+    ));
+}
+
+#[test]
+fn enum_variant_changed() {
+    let old = ConnectionState::Idle;
+    let new = ConnectionState::Connecting { attempt: 1 };
+
+    let diff = diff_changes(&old, &new);
+
+    assert!(diff.iter().any(|ch| matches!(
+        ch,
+        ConnectionStateChange::VariantChanged {
+            old: ConnectionStateSnapshot::Idle,
+            new: ConnectionStateSnapshot::Connecting { attempt: &1 },
+        }
+    )));
+    assert!(!diff
+        .iter()
+        .any(|ch| matches!(ch, ConnectionStateChange::Connecting(_))));
+}
+
+#[test]
+fn enum_unchanged_unit_variant() {
+    let diff = diff_changes(&ConnectionState::Idle, &ConnectionState::Idle);
+    assert!(diff.is_empty());
+}
+
+#[test]
+fn enum_nested_variant_changes_with_macro() {
+    let old = ConnectionState::Connected(Session {
+        token: "abc".to_string(),
+        peers: vec![1],
+    });
+    let new = ConnectionState::Connected(Session {
+        token: "xyz".to_string(),
+        peers: vec![1, 2],
+    });
+
+    let diff = diff_changes(&old, &new);
+
+    let mut token_changed = false;
+    let mut peer_added = false;
+    for change in &diff {
+        if let ConnectionStateChange::Connected(ConnectionStateConnectedChange::item0(inner)) =
+            change
+        {
+            changed!(inner;
+                Session.token(token) => {
+                    assert_eq!(token.as_ref(), "xyz");
+                    token_changed = true;
+                };
+                Session.peers(AddedAt(1, &2, _)) => {
+                    peer_added = true;
+                };
+            );
+        }
+    }
+
+    assert!(token_changed);
+    assert!(peer_added);
+
+    let old = ConnectionState::Connecting { attempt: 1 };
+    let new = ConnectionState::Connecting { attempt: 3 };
+    let mut attempt = None;
+    for change in &diff_changes(&old, &new) {
+        changed!(change;
+            ConnectionState.Connecting.attempt(v) => { attempt = Some(**v); };
+        );
+    }
+    assert_eq!(attempt, Some(3));
+}
+
+#[test]
+fn variant_changes_are_named_per_enum() {
+    let old = Message::Config(Config {
+        mailing: None,
+        nickname: None,
+    });
+    let new = Message::Config(Config {
+        mailing: None,
+        nickname: Some("ally".into()),
+    });
+    let mut nickname = None;
+    for change in &diff_changes(&old, &new) {
+        changed!(change;
+            Message.Config.item0.nickname(differs::OptionChanged::Set(n)) => {
+                nickname = Some(n.to_string());
+            };
+        );
+    }
+    assert_eq!(nickname.as_deref(), Some("ally"));
+    assert!(diff_changes(&new, &Message::Quit)
+        .iter()
+        .all(|c| !matches!(c, MessageChange::Config(_))));
+
+    assert!(matches!(
+        diff_changes(&Lamp::On { watts: 40 }, &Lamp::On { watts: 60 }).as_slice(),
+        [
+            LampChange::self_(_),
+            LampChange::On(LampOnChange::watts(&60))
+        ]
+    ));
+    assert!(matches!(
+        diff_changes(&Fan::On { speed: 1 }, &Fan::On { speed: 2 }).as_slice(),
+        [FanChange::self_(_), FanChange::On(FanOnChange::speed(&2))]
+    ));
+}
+
+#[test]
+fn raw_identifier_variant_fields() {
+    let old = Token::Word {
+        r#type: 1,
+        text: "a".into(),
+    };
+    let new = Token::Word {
+        r#type: 2,
+        text: "a".into(),
+    };
+    let diff = diff_changes(&old, &new);
+    assert!(matches!(
+        diff.as_slice(),
+        [
+            TokenChange::self_(_),
+            TokenChange::Word(TokenWordChange::r#type(&2))
+        ]
+    ));
+
+    let patch: Vec<_> = diff.into_iter().map(IntoOwned::into_owned).collect();
+    let mut patched = old.clone();
+    patched.apply_changes(&patch).unwrap();
+    assert_eq!(patched, new);
+    assert!(Token::End.apply_changes(&patch).is_err());
+}
+
+#[test]
+fn aliased_vec_fields_use_the_library_impl() {
+    let old = Spellings {
+        direct: vec![1, 2, 3],
+        aliased: vec![1, 2, 3],
+        ports: vec![80],
+    };
+    let new = Spellings {
+        direct: vec![3, 1, 2],
+        aliased: vec![3, 1, 2],
+        ports: vec![80],
+    };
+    let diff = diff_changes(&old, &new);
+
+    /* written `Vec<u32>`: hash matching, every shift a `Moved` */
+    let direct: Vec<&Changed<'_, u32, &u32>> = diff
+        .iter()
+        .filter_map(|c| match c {
+            SpellingsChange::direct(c) => Some(c),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        direct,
+        [&Moved(&3, 2, 0), &Moved(&1, 0, 1), &Moved(&2, 1, 2)]
+    );
+
+    /* behind an alias: `Vec`'s `HasChanges` impl, a Myers edit script */
+    let aliased: Vec<&Changed<'_, u32, &u32>> = diff
+        .iter()
+        .filter_map(|c| match c {
+            SpellingsChange::aliased(c) => Some(c),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(aliased, [&Moved(&3, 2, 0)]);
+}
+
+#[test]
+fn aliased_scalar_elements_are_replaced() {
+    let old = Spellings {
+        direct: Vec::new(),
+        aliased: Vec::new(),
+        ports: vec![80, 443],
+    };
+    let new = Spellings {
+        ports: vec![80, 8443],
+        ..old.clone()
+    };
+    let diff = diff_changes(&old, &new);
+    /* `Port` is a `u16`: no `ModifiedAt` pairing of the differing slot */
+    assert!(matches!(
+        diff.as_slice(),
+        [
+            SpellingsChange::self_(_),
+            SpellingsChange::ports(AddedAt(1, &8443, 0)),
+            SpellingsChange::ports(RemovedAt(1, &443, 0)),
+        ]
+    ));
+}
+
+#[test]
+fn str_pointers_patch_whole() {
+    let old = Names {
+        boxed: "a".into(),
+        shared: "b".into(),
+        label: "c".into(),
+        count: 1.into(),
+    };
+    let new = Names {
+        boxed: "x".into(),
+        shared: "y".into(),
+        label: "z".into(),
+        count: 2.into(),
+    };
+    let diff = diff_changes(&old, &new);
+    assert!(diff
+        .iter()
+        .any(|c| matches!(c, NamesChange::boxed(b) if b == "x")));
+
+    let patch: Vec<_> = diff.into_iter().map(IntoOwned::into_owned).collect();
+    let mut patched = old.clone();
+    patched.apply_changes(&patch).unwrap();
+    assert_eq!(patched, new);
+}
+
+#[test]
+fn phantom_fields_never_change() {
+    let old = Typed::<String> {
+        id: 1,
+        _kind: PhantomData,
+    };
+    let new = Typed {
+        id: 2,
+        ..old.clone()
+    };
+    assert!(matches!(
+        diff_changes(&old, &new).as_slice(),
+        [TypedChange::self_(_), TypedChange::id(&2)]
+    ));
+
+    let marker = PhantomData::<str>;
+    assert!(diff_changes(&marker, &marker).is_empty());
+    let mut patched = marker.into_owned();
+    patched.apply_changes(&[]).unwrap();
+}
+
+#[test]
+fn newtype_struct_changes() {
+    let diff = diff_changes(&UserId(1), &UserId(2));
+    assert!(matches!(
+        diff.as_slice(),
+        [
+            UserIdChange::self_(UserIdSnapshot(&2)),
+            UserIdChange::item0(&2)
+        ]
+    ));
+
+    let old = Tags(HashSet::from(["a".to_string()]));
+    let new = Tags(HashSet::from(["b".to_string()]));
+    let mut added = false;
+    for change in &diff_changes(&old, &new) {
+        changed!(change;
+            Tags.item0(Added(tag)) => { added = tag.as_str() == "b"; };
+        );
+    }
+    assert!(added);
+}
+
+#[test]
+fn tuple_struct_items_and_skip() {
+    let old = Labeled("a".to_string(), 1, UserId(1));
+    let new = Labeled("a".to_string(), 2, UserId(5));
+
+    let diff = diff_changes(&old, &new);
+
+    assert!(diff
+        .iter()
+        .any(|ch| matches!(ch, LabeledChange::item2(UserIdChange::item0(&5)))));
+    assert!(!diff.iter().any(|ch| matches!(ch, LabeledChange::item0(_))));
+}
+
+#[test]
+fn unit_struct_never_changes() {
+    assert!(diff_changes(&Marker, &Marker).is_empty());
+}
+
+#[test]
+fn generic_struct_nested_changes() {
+    let old = Versioned {
+        version: 1,
+        value: Leaf { value: 1 },
+    };
+    let new = Versioned {
+        version: 2,
+        value: Leaf { value: 5 },
+    };
+
+    let diff = diff_changes(&old, &new);
+
+    assert!(diff
+        .iter()
+        .any(|ch| matches!(ch, VersionedChange::version(&2))));
+    assert!(diff
+        .iter()
+        .any(|ch| matches!(ch, VersionedChange::value(LeafChange::value(&5)))));
+
+    let old = Tagged { items: vec!['a'] };
+    let new = Tagged {
+        items: vec!['a', 'b'],
+    };
+    assert!(matches!(
+        diff_changes(&old, &new).as_slice(),
+        [
+            TaggedChange::self_(_),
+            TaggedChange::items(AddedAt(1, &'b', _))
+        ]
+    ));
+}
+
+#[test]
+fn lifetime_and_const_generic_structs() {
+    let (a, b) = (String::from("a"), String::from("b"));
+    let (old, new) = (Borrowed { name: &a }, Borrowed { name: &b });
+    let diff = diff_changes(&old, &new);
+    assert!(diff
+        .iter()
+        .any(|ch| matches!(ch, BorrowedChange::name(&"b"))));
+
+    let diff = diff_changes(&Fixed { data: [1, 2] }, &Fixed { data: [1, 3] });
+    assert!(diff
+        .iter()
+        .any(|ch| matches!(ch, FixedChange::data(ModifiedAt(1, &3, _)))));
+}
+
+/// A field type that is not `Debug`.
+#[derive(Diff, Clone, PartialEq)]
+struct Mute {
+    level: u8,
+}
+
+#[test]
+fn generic_params_need_not_be_debug() {
+    let old = Versioned {
+        version: 1,
+        value: Mute { level: 1 },
+    };
+    let new = Versioned {
+        version: 1,
+        value: Mute { level: 3 },
+    };
+    let diff = diff_changes(&old, &new);
+    assert!(matches!(
+        diff.as_slice(),
+        [
+            VersionedChange::self_(_),
+            VersionedChange::value(MuteChange::self_(_)),
+            VersionedChange::value(MuteChange::level(&3)),
+        ]
+    ));
+
+    let old = Slot::Full(Mute { level: 1 });
+    assert!(diff_changes(&old, &Slot::Empty)
+        .iter()
+        .any(|ch| matches!(ch, SlotChange::VariantChanged { .. })));
+}
+
+#[test]
+fn generic_bound_override_and_enum() {
+    let old = Bounded {
+        inner: Leaf { value: 1 },
+    };
+    let new = Bounded {
+        inner: Leaf { value: 2 },
+    };
+    assert!(diff_changes(&old, &new)
+        .iter()
+        .any(|ch| matches!(ch, BoundedChange::inner(LeafChange::value(&2)))));
+
+    let old = Slot::Full(Leaf { value: 1 });
+    let new = Slot::Full(Leaf { value: 2 });
+    assert!(diff_changes(&old, &new).iter().any(|ch| matches!(
+        ch,
+        SlotChange::Full(SlotFullChange::item0(LeafChange::value(&2)))
+    )));
+    assert!(diff_changes(&old, &Slot::Empty)
+        .iter()
+        .any(|ch| matches!(ch, SlotChange::VariantChanged { .. })));
+}
+
+#[test]
+fn old_new_field_carries_both_values() {
+    let old = Account {
+        username: "alice".into(),
+        email: "a@x.io".into(),
+        profile: Profile {
+            age: 30,
+            bio: "hi".into(),
+        },
+    };
+    let mut new = old.clone();
+    new.username = "alicia".into();
+    new.email = "alicia@x.io".into();
+
+    let changes = diff_changes(&old, &new);
+    assert!(changes.iter().any(|ch| matches!(
+        ch,
+        AccountChange::username(o, n) if *o == "alice" && *n == "alicia"
+    )));
+    /* fields without the attribute keep the single `new` payload */
+    assert!(changes
+        .iter()
+        .any(|ch| matches!(ch, AccountChange::email(n) if n == "alicia@x.io")));
+}
+
+#[test]
+fn container_old_new_with_changed_macro() {
+    let old = Account {
+        username: "alice".into(),
+        email: "a@x.io".into(),
+        profile: Profile {
+            age: 30,
+            bio: "hi".into(),
+        },
+    };
+    let mut new = old.clone();
+    new.username = "alicia".into();
+    new.profile.age = 31;
+
+    let mut seen = Vec::new();
+    for change in &diff_changes(&old, &new) {
+        changed!(change;
+            Account.username(old => new) => {
+                seen.push(format!("username: {old} -> {new}"));
+            };
+            Account.profile.age(&old => &new) => {
+                seen.push(format!("age: {old} -> {new}"));
+            };
+        );
+    }
+    assert_eq!(seen, ["username: alice -> alicia", "age: 30 -> 31"]);
+}
+
+fn member(id: u32, name: &str) -> Member {
+    Member {
+        id,
+        name: name.into(),
+    }
+}
+
+#[test]
+fn vec_in_place_mutation_is_modified_at() {
+    let old = Team {
+        members: vec![member(1, "ann"), member(2, "bo")],
+    };
+    let mut new = old.clone();
+    new.members[0].name = "anne".into();
+
+    let changes = diff_changes(&old, &new);
+    assert_eq!(changes.len(), 2, "{changes:?}");
+    assert!(matches!(changes[0], TeamChange::self_(_)));
+    match &changes[1] {
+        TeamChange::members(ModifiedAt(0, m, subs)) => {
+            assert_eq!(m.name, "anne");
+            assert!(subs
+                .iter()
+                .any(|c| matches!(c, MemberChange::name(n) if n == "anne")));
+        }
+        other => panic!("unexpected {other:?}"),
+    }
+}
+
+#[test]
+fn vec_modified_at_only_pairs_free_slots() {
+    let old = Team {
+        members: vec![member(1, "ann"), member(2, "bo")],
+    };
+    let new = Team {
+        members: vec![member(2, "bo"), member(3, "cy"), member(4, "di")],
+    };
+
+    let changes = diff_changes(&old, &new);
+    /* `bo` moved into `ann`'s slot, so nothing is paired up there */
+    assert!(changes
+        .iter()
+        .any(|c| matches!(c, TeamChange::members(Moved(m, 1, 0)) if m.id == 2)));
+    assert!(changes
+        .iter()
+        .any(|c| matches!(c, TeamChange::members(AddedAt(1, m, _)) if m.id == 3)));
+    assert!(changes
+        .iter()
+        .any(|c| matches!(c, TeamChange::members(AddedAt(2, m, _)) if m.id == 4)));
+    assert!(changes
+        .iter()
+        .any(|c| matches!(c, TeamChange::members(RemovedAt(0, m, _)) if m.id == 1)));
+    assert!(!changes
+        .iter()
+        .any(|c| matches!(c, TeamChange::members(ModifiedAt(..)))));
+}
+
+/// Equality sees `note`, the diff does not.
+#[derive(Diff, Clone, Debug, PartialEq, Eq, Hash)]
+struct Noted {
+    id: u32,
+    #[differs(skip)]
+    note: u32,
+}
+
+#[derive(Diff, Clone, Debug, PartialEq)]
+struct Notes {
+    v: Vec<Noted>,
+}
+
+#[test]
+fn vec_unequal_elements_without_nested_changes_are_replaced() {
+    let old = Notes {
+        v: vec![Noted { id: 1, note: 1 }],
+    };
+    let new = Notes {
+        v: vec![Noted { id: 1, note: 2 }],
+    };
+
+    let changes = diff_changes(&old, &new);
+    assert!(changes
+        .iter()
+        .any(|c| matches!(c, NotesChange::v(AddedAt(0, n, 0)) if n.note == 2)));
+    assert!(changes
+        .iter()
+        .any(|c| matches!(c, NotesChange::v(RemovedAt(0, n, 0)) if n.note == 1)));
+}
+
+fn player(id: u32, score: u32) -> Player {
+    Player {
+        id,
+        score,
+        badges: HashSet::new(),
+    }
+}
+
+#[test]
+fn keyed_vec_pairs_elements_by_key() {
+    let old = League {
+        players: vec![player(1, 10), player(2, 20), player(3, 30)],
+        reserves: vec![],
+    };
+    let new = League {
+        players: vec![player(3, 30), player(1, 15), player(4, 0)],
+        reserves: vec![],
+    };
+
+    let changes = diff_changes(&old, &new);
+    assert!(changes
+        .iter()
+        .any(|c| matches!(c, LeagueChange::players(Moved(p, 2, 0)) if p.id == 3)));
+    assert!(changes.iter().any(|c| matches!(
+        c,
+        LeagueChange::players(ModifiedAt(1, p, subs))
+            if p.id == 1 && matches!(subs[..], [PlayerChange::self_(_), PlayerChange::score(&15)])
+    )));
+    assert!(changes
+        .iter()
+        .any(|c| matches!(c, LeagueChange::players(AddedAt(2, p, _)) if p.id == 4)));
+    assert!(changes
+        .iter()
+        .any(|c| matches!(c, LeagueChange::players(RemovedAt(1, p, _)) if p.id == 2)));
+    /* `1` shifted and changed: one `Moved` plus one `ModifiedAt` */
+    assert!(changes
+        .iter()
+        .any(|c| matches!(c, LeagueChange::players(Moved(p, 0, 1)) if p.id == 1)));
+    assert_eq!(changes.len(), 6, "{changes:?}");
+}
+
+#[test]
+fn keyed_vec_with_key_fn() {
+    let old = League {
+        players: vec![],
+        reserves: vec![player(7, 1)],
+    };
+    let mut new = old.clone();
+    new.reserves[0].badges.insert("mvp".into());
+
+    let changes = diff_changes(&old, &new);
+    assert_eq!(changes.len(), 2, "{changes:?}");
+    match &changes[1] {
+        LeagueChange::reserves(ModifiedAt(0, p, subs)) => {
+            assert_eq!(p.id, 7);
+            assert!(subs
+                .iter()
+                .any(|c| matches!(c, PlayerChange::badges(Added(b)) if b.as_str() == "mvp")));
+        }
+        other => panic!("unexpected {other:?}"),
+    }
+}
+
+#[test]
+fn lcs_algorithms_on_partial_eq_elements() {
+    let old = Playlist {
+        tracks: vec![1.0, 2.0, 3.0, 4.0],
+        players: vec![player(1, 10), player(2, 20)],
+    };
+    let new = Playlist {
+        tracks: vec![2.0, 3.0, 4.0, 1.0],
+        players: vec![player(1, 10), player(2, 25), player(3, 0)],
+    };
+
+    let changes = diff_changes(&old, &new);
+    let tracks: Vec<_> = changes
+        .iter()
+        .filter_map(|c| match c {
+            PlaylistChange::tracks(t) => Some(t.clone()),
+            _ => None,
+        })
+        .collect();
+    /* only the element that actually left its run is a move */
+    assert_eq!(tracks, [Moved(&1.0, 0, 3)]);
+
+    assert!(changes.iter().any(|c| matches!(
+        c,
+        PlaylistChange::players(ModifiedAt(1, p, subs))
+            if p.id == 2 && subs.iter().any(|s| matches!(s, PlayerChange::score(&25)))
+    )));
+    assert!(changes
+        .iter()
+        .any(|c| matches!(c, PlaylistChange::players(AddedAt(2, p, _)) if p.id == 3)));
+}
+
+#[test]
+fn added_and_removed_at_count_duplicates() {
+    let old = Bag {
+        items: vec!['X', 'Y'],
+    };
+    let new = Bag {
+        items: vec!['X', 'A', 'Y', 'A'],
+    };
+    let changes = diff_changes(&old, &new);
+    assert!(changes
+        .iter()
+        .any(|c| matches!(c, BagChange::items(AddedAt(1, &'A', 0)))));
+    assert!(changes
+        .iter()
+        .any(|c| matches!(c, BagChange::items(AddedAt(3, &'A', 1)))));
+
+    let old = Bag {
+        items: vec!['B', 'B', 'B'],
+    };
+    let new = Bag { items: vec![] };
+    let removed: Vec<_> = diff_changes(&old, &new)
+        .into_iter()
+        .filter_map(|c| match c {
+            BagChange::items(RemovedAt(i, _, n)) => Some((i, n)),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(removed, [(0, 0), (1, 1), (2, 2)]);
+}
+
+#[test]
+fn ranges_collapse_contiguous_runs() {
+    use differs::Changed::{AddedRange, RemovedRange};
+
+    let old = Log {
+        lines: (0..10).collect(),
+    };
+    let new = Log {
+        lines: (0..10).chain(100..105).collect(),
+    };
+    let changes = diff_changes(&old, &new);
+    assert!(matches!(
+        &changes[..],
+        [LogChange::self_(_), LogChange::lines(AddedRange(10, added))] if added == &[100, 101, 102, 103, 104]
+    ));
+
+    let new = Log {
+        lines: vec![0, 1, 2, 9],
+    };
+    let changes = diff_changes(&old, &new);
+    assert!(changes
+        .iter()
+        .any(|c| matches!(c, LogChange::lines(RemovedRange(3, gone)) if gone.len() == 6)));
+
+    /* a lone insertion stays an `AddedAt` */
+    let mut new = old.clone();
+    new.lines.push(42);
+    assert!(diff_changes(&old, &new)
+        .iter()
+        .any(|c| matches!(c, LogChange::lines(AddedAt(10, &42, 0)))));
+}
+
+#[test]
+fn map_entries_carry_old_new_and_nested_changes() {
+    let old = Prefs {
+        prefs: HashMap::from([("theme", "dark")]),
+    };
+    let new = Prefs {
+        prefs: HashMap::from([("theme", "light")]),
+    };
+    let mut seen = None;
+    for change in &diff_changes(&old, &new) {
+        changed!(change;
+            Prefs.prefs(ChangedEntry(k, old, new)) => {
+                seen = Some((**k, **old, **new));
+            };
+        );
+    }
+    assert_eq!(seen, Some(("theme", "dark", "light")));
+
+    let old = Directory {
+        entries: HashMap::from([("a".to_string(), Leaf { value: 1 })]),
+    };
+    let new = Directory {
+        entries: HashMap::from([("a".to_string(), Leaf { value: 2 })]),
+    };
+    let changes = diff_changes(&old, &new);
+    assert!(changes.iter().any(|c| matches!(
+        c,
+        DirectoryChange::entries(ModifiedEntry(k, subs))
+            if k.as_str() == "a" && subs.iter().any(|s| matches!(s, LeafChange::value(&2)))
+    )));
+}
+
+#[test]
+fn btree_collections_report_in_key_order() {
+    let old = Catalog {
+        prices: BTreeMap::from([("b".into(), 2), ("c".into(), 3), ("e".into(), 5)]),
+        sections: BTreeSet::from([1, 3, 5]),
+        queue: VecDeque::new(),
+    };
+    let new = Catalog {
+        prices: BTreeMap::from([("a".into(), 1), ("c".into(), 4), ("d".into(), 4)]),
+        sections: BTreeSet::from([2, 3, 4]),
+        queue: VecDeque::new(),
+    };
+
+    let changes = diff_changes(&old, &new);
+    let prices: Vec<String> = changes
+        .iter()
+        .filter_map(|c| match c {
+            CatalogChange::prices(AddedEntry(k, _)) => Some(format!("+{k}")),
+            CatalogChange::prices(RemovedEntry(k, _)) => Some(format!("-{k}")),
+            CatalogChange::prices(ChangedEntry(k, o, n)) => Some(format!("{k}:{o}->{n}")),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(prices, ["+a", "-b", "c:3->4", "+d", "-e"]);
+
+    let sections: Vec<String> = changes
+        .iter()
+        .filter_map(|c| match c {
+            CatalogChange::sections(Added(v)) => Some(format!("+{v}")),
+            CatalogChange::sections(Removed(v)) => Some(format!("-{v}")),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(sections, ["-1", "+2", "+4", "-5"]);
+}
+
+#[test]
+fn vec_deque_fields() {
+    let old = Catalog {
+        prices: BTreeMap::new(),
+        sections: BTreeSet::new(),
+        queue: VecDeque::from(['a', 'b']),
+    };
+    let mut new = old.clone();
+    new.queue.push_front('z');
+    new.queue.pop_back();
+
+    let changes = diff_changes(&old, &new);
+    assert!(changes
+        .iter()
+        .any(|c| matches!(c, CatalogChange::queue(AddedAt(0, &'z', 0)))));
+    assert!(changes
+        .iter()
+        .any(|c| matches!(c, CatalogChange::queue(Moved(&'a', 0, 1)))));
+    assert!(changes
+        .iter()
+        .any(|c| matches!(c, CatalogChange::queue(RemovedAt(1, &'b', 0)))));
+}
+
+#[cfg(feature = "indexmap")]
+#[test]
+fn indexmap_collections() {
+    let old = Indexed {
+        map: indexmap::IndexMap::from([("x", Leaf { value: 1 })]),
+        set: indexmap::IndexSet::from([1, 2]),
+    };
+    let new = Indexed {
+        map: indexmap::IndexMap::from([("x", Leaf { value: 2 })]),
+        set: indexmap::IndexSet::from([2, 3]),
+    };
+
+    let changes = diff_changes(&old, &new);
+    assert!(changes.iter().any(|c| matches!(
+        c,
+        IndexedChange::map(ModifiedEntry(&"x", subs)) if matches!(subs[..], [LeafChange::self_(_), LeafChange::value(&2)])
+    )));
+    assert!(changes
+        .iter()
+        .any(|c| matches!(c, IndexedChange::set(Removed(&1)))));
+    assert!(changes
+        .iter()
+        .any(|c| matches!(c, IndexedChange::set(Added(&3)))));
+}
+
+#[test]
+fn option_fields_set_unset_and_inner() {
+    use differs::OptionChanged::{Inner, Set, Unset};
+
+    let address = Address {
+        street: "1 Main".into(),
+        city: "Paris".into(),
+        zip: "75001".into(),
+    };
+    let old = Config {
+        mailing: None,
+        nickname: Some("al".into()),
+    };
+    let new = Config {
+        mailing: Some(address.clone()),
+        nickname: None,
+    };
+    let changes = diff_changes(&old, &new);
+    assert!(changes
+        .iter()
+        .any(|c| matches!(c, ConfigChange::mailing(Set(a)) if a.city == "Paris")));
+    assert!(changes
+        .iter()
+        .any(|c| matches!(c, ConfigChange::nickname(Unset(n)) if n.as_str() == "al")));
+
+    let old = new.clone();
+    let mut new = old.clone();
+    new.mailing.as_mut().unwrap().city = "Lyon".into();
+    new.nickname = Some("ally".into());
+    let mut city = None;
+    for change in &diff_changes(&old, &new) {
+        changed!(change;
+            Config.mailing(Inner(AddressChange::city(c))) => { city = Some(c.to_string()); };
+            Config.nickname(Set(n)) => { assert_eq!(n.as_str(), "ally"); };
+        );
+    }
+    assert_eq!(city.as_deref(), Some("Lyon"));
+}
+
+#[test]
+fn smart_pointers_are_transparent() {
+    use std::{rc::Rc, sync::Arc};
+
+    let settings = Arc::new(Address {
+        street: "1 Main".into(),
+        city: "Paris".into(),
+        zip: "75001".into(),
+    });
+    let old = Shared {
+        root: Box::new(Leaf { value: 1 }),
+        settings: settings.clone(),
+        label: "a".into(),
+        counts: Rc::new(vec![1]),
+    };
+    let new = Shared {
+        root: Box::new(Leaf { value: 2 }),
+        settings: Arc::new(Address {
+            city: "Rome".into(),
+            ..(*settings).clone()
+        }),
+        label: String::from("b").into(),
+        counts: Rc::new(vec![1, 2]),
+    };
+
+    let changes = diff_changes(&old, &new);
+    assert!(changes
+        .iter()
+        .any(|c| matches!(c, SharedChange::root(LeafChange::value(&2)))));
+    assert!(changes
+        .iter()
+        .any(|c| matches!(c, SharedChange::settings(AddressChange::city(c)) if c == "Rome")));
+    assert!(changes
+        .iter()
+        .any(|c| matches!(c, SharedChange::label(l) if l == "b")));
+    assert!(changes
+        .iter()
+        .any(|c| matches!(c, SharedChange::counts(AddedAt(1, &2, 0)))));
+
+    /* `ptr_eq`: the same allocation is skipped without being compared */
+    let same = Shared {
+        settings: settings.clone(),
+        ..new.clone()
+    };
+    assert!(!diff_changes(&old, &same)
+        .iter()
+        .any(|c| matches!(c, SharedChange::settings(_))));
+}
+
+#[test]
+fn arrays_and_tuples_are_diffed_element_wise() {
+    use differs::TupleChanged::{item0, item1, item2};
+
+    let old = Geometry {
+        corners: [Leaf { value: 0 }, Leaf { value: 1 }, Leaf { value: 2 }],
+        origin: (0.0, 0.0),
+        named: ("a".into(), Leaf { value: 0 }, vec![]),
+    };
+    let mut new = old.clone();
+    new.corners[1].value = 10;
+    new.origin.1 = 5.0;
+    new.named.0 = "b".into();
+    new.named.1.value = 7;
+    new.named.2.push(9);
+
+    let changes = diff_changes(&old, &new);
+    assert!(changes.iter().any(|c| matches!(
+        c,
+        GeometryChange::corners(ModifiedAt(1, _, subs)) if matches!(subs[..], [LeafChange::self_(_), LeafChange::value(&10)])
+    )));
+    assert!(!changes
+        .iter()
+        .any(|c| matches!(c, GeometryChange::corners(ModifiedAt(0 | 2, ..)))));
+    assert!(changes
+        .iter()
+        .any(|c| matches!(c, GeometryChange::origin(item1(&5.0)))));
+    assert!(!changes
+        .iter()
+        .any(|c| matches!(c, GeometryChange::origin(item0(_)))));
+    assert!(changes
+        .iter()
+        .any(|c| matches!(c, GeometryChange::named(item0(s)) if s == "b")));
+    assert!(changes
+        .iter()
+        .any(|c| matches!(c, GeometryChange::named(item1(LeafChange::value(&7))))));
+    assert!(changes
+        .iter()
+        .any(|c| matches!(c, GeometryChange::named(item2(AddedAt(0, &9, 0))))));
+}
+
+#[test]
+fn tuple_paths_are_the_fields_of_their_tuple() {
+    use differs::{FieldName, HasFields};
+
+    let old = Geometry {
+        corners: [Leaf { value: 0 }, Leaf { value: 1 }, Leaf { value: 2 }],
+        origin: (0.0, 0.0),
+        named: ("a".into(), Leaf { value: 0 }, vec![]),
+    };
+    let mut new = old.clone();
+    new.origin.1 = 5.0;
+    new.named.1.value = 7;
+    new.named.2.push(9);
+
+    let fields = Geometry::fields();
+    let paths: BTreeSet<String> = diff_changes(&old, &new)
+        .iter()
+        .map(|c| c.path().as_str().to_owned())
+        .collect();
+    let expected = [FieldName::static_lit(""), fields.origin(), fields.named()];
+    assert_eq!(paths, expected.map(|p| p.as_str().to_owned()).into());
+}
+
+type Port = u16;
+
+#[derive(Diff, Clone, Debug, PartialEq)]
+struct Endpoint {
+    port: Port,
+    timeout: std::time::Duration,
+    root: std::path::PathBuf,
+    addr: std::net::IpAddr,
+    label: std::string::String,
+    backup: Option<Port>,
+    ports: Vec<Port>,
+    limits: HashMap<String, std::time::Duration>,
+}
+
+#[test]
+fn std_scalars_behind_paths_and_aliases() {
+    use differs::OptionChanged::Set;
+    use std::time::Duration;
+
+    let old = Endpoint {
+        port: 80,
+        timeout: Duration::from_secs(1),
+        root: "/srv".into(),
+        addr: [127, 0, 0, 1].into(),
+        label: "web".into(),
+        backup: Some(8080),
+        ports: vec![1, 2],
+        limits: HashMap::from([("read".into(), Duration::from_secs(1))]),
+    };
+    let new = Endpoint {
+        port: 443,
+        timeout: Duration::from_secs(5),
+        root: "/var/www".into(),
+        addr: [10, 0, 0, 1].into(),
+        label: "api".into(),
+        backup: Some(8443),
+        ports: vec![1, 3],
+        limits: HashMap::from([("read".into(), Duration::from_secs(2))]),
+    };
+
+    let changes = diff_changes(&old, &new);
+    let has = |f: &dyn Fn(&EndpointChange) -> bool| changes.iter().any(f);
+    assert!(has(&|c| matches!(c, EndpointChange::port(&443))));
+    assert!(has(
+        &|c| matches!(c, EndpointChange::timeout(d) if d.as_secs() == 5)
+    ));
+    assert!(has(
+        &|c| matches!(c, EndpointChange::root(p) if *p == &new.root)
+    ));
+    assert!(has(
+        &|c| matches!(c, EndpointChange::addr(a) if *a == &new.addr)
+    ));
+    assert!(has(
+        &|c| matches!(c, EndpointChange::label(s) if s == "api")
+    ));
+    /* scalars are replaced, never modified in place */
+    assert!(has(&|c| matches!(c, EndpointChange::backup(Set(&8443)))));
+    assert!(has(&|c| matches!(
+        c,
+        EndpointChange::ports(AddedAt(1, &3, 0))
+    )));
+    assert!(has(&|c| matches!(
+        c,
+        EndpointChange::ports(RemovedAt(1, &2, 0))
+    )));
+    assert!(!has(&|c| matches!(
+        c,
+        EndpointChange::ports(ModifiedAt(..))
+    )));
+    assert!(has(&|c| matches!(
+        c,
+        EndpointChange::limits(ChangedEntry(k, _, d)) if k.as_str() == "read" && d.as_secs() == 2
+    )));
+}
+
+#[test]
+fn std_types_implement_has_changes() {
+    use differs::OptionChanged::{Inner, Set};
+
+    assert_eq!(diff_changes(&1u8, &2u8), [&2]);
+    assert!(diff_changes(&"a".to_string(), &"a".to_string()).is_empty());
+    assert_eq!(diff_changes(&Box::new(1), &Box::new(2)), [&2]);
+
+    let (old, new) = (vec![1, 2, 3], vec![1, 3]);
+    assert_eq!(diff_changes(&old, &new), [Changed::RemovedAt(1, &2, 0)]);
+
+    let old = BTreeMap::from([("a", 1), ("b", 2)]);
+    let new = BTreeMap::from([("a", 1), ("b", 3), ("c", 4)]);
+    assert_eq!(
+        diff_changes(&old, &new),
+        [ChangedEntry(&"b", &2, &3), AddedEntry(&"c", &4)]
+    );
+
+    assert_eq!(diff_changes(&Some(1), &Some(2)), [Set(&2)]);
+    let old = Some(Leaf { value: 1 });
+    let new = Some(Leaf { value: 2 });
+    assert!(diff_changes(&old, &new)
+        .iter()
+        .any(|c| matches!(c, Inner(LeafChange::value(&2)))));
+
+    let (old, new) = ((1, "x".to_string()), (2, "x".to_string()));
+    assert!(matches!(
+        diff_changes(&old, &new)[..],
+        [differs::TupleChanged::item0(&2)]
+    ));
+}
+
+/// Stands in for a foreign type that cannot implement `HasChanges`.
+#[derive(Clone, Debug, PartialEq)]
+struct Stamp(u64);
+
+/// Reports how far a `Stamp` moved instead of the new value.
+mod stamp_delta {
+    pub type Change<'a> = i64;
+
+    pub fn collect_changes(old: &super::Stamp, new: &super::Stamp, out: &mut Vec<i64>) {
+        if old != new {
+            out.push(new.0 as i64 - old.0 as i64);
+        }
+    }
+}
+
+/// Only reports a `Stamp` that moved by more than a minute.
+fn moved_far(old: &Stamp, new: &Stamp) -> bool {
+    old.0.abs_diff(new.0) > 60
+}
+
+#[derive(Diff, Clone, Debug, PartialEq)]
+struct Event {
+    #[differs(scalar)]
+    created: Stamp,
+    #[differs(opaque, old_new)]
+    checkpoints: Vec<Stamp>,
+    #[differs(with_module = stamp_delta)]
+    updated: Stamp,
+    #[differs(with = moved_far)]
+    seen: Stamp,
+    #[differs(with = moved_far)]
+    synced: Stamp,
+}
+
+#[test]
+fn scalar_and_with_fields() {
+    let old = Event {
+        created: Stamp(1),
+        checkpoints: vec![Stamp(1)],
+        updated: Stamp(10),
+        seen: Stamp(0),
+        synced: Stamp(0),
+    };
+    let new = Event {
+        created: Stamp(2),
+        checkpoints: vec![Stamp(1), Stamp(2)],
+        updated: Stamp(7),
+        seen: Stamp(30),
+        synced: Stamp(90),
+    };
+
+    let changes = diff_changes(&old, &new);
+    assert!(changes
+        .iter()
+        .any(|c| matches!(c, EventChange::created(&Stamp(2)))));
+    assert!(changes.iter().any(|c| matches!(
+        c,
+        EventChange::checkpoints(o, n) if o.len() == 1 && n.len() == 2
+    )));
+    assert!(changes
+        .iter()
+        .any(|c| matches!(c, EventChange::updated(-3))));
+    assert!(!changes.iter().any(|c| matches!(c, EventChange::seen(_))));
+    assert!(changes
+        .iter()
+        .any(|c| matches!(c, EventChange::synced(&Stamp(90)))));
+    assert!(diff_changes(&old, &old.clone()).is_empty());
+}
+
+fn same_ignoring_case(old: &str, new: &str) -> bool {
+    old.eq_ignore_ascii_case(new)
+}
+
+#[derive(Diff, Clone, Debug, PartialEq)]
+struct Reading {
+    #[differs(epsilon = 1e-9)]
+    value: f64,
+    #[differs(ulps = 4)]
+    gain: f32,
+    #[differs(eq = same_ignoring_case)]
+    unit: String,
+}
+
+#[derive(Diff, Clone, Debug, PartialEq)]
+enum Sample {
+    Level(#[differs(epsilon = 0.5)] f64),
+    Off,
+}
+
+#[test]
+fn custom_eq_and_float_tolerance() {
+    let old = Reading {
+        value: 0.1 + 0.2,
+        gain: 1.0,
+        unit: "mV".into(),
+    };
+    /* noise only: no field change and no `self_` */
+    let noisy = Reading {
+        value: 0.3,
+        gain: f32::from_bits(1.0f32.to_bits() + 3),
+        unit: "MV".into(),
+    };
+    assert_ne!(old, noisy);
+    assert!(diff_changes(&old, &noisy).is_empty());
+
+    let new = Reading {
+        value: 0.4,
+        gain: f32::from_bits(1.0f32.to_bits() + 5),
+        unit: "V".into(),
+    };
+    let changes = diff_changes(&old, &new);
+    assert!(matches!(changes[0], ReadingChange::self_(_)));
+    assert!(changes
+        .iter()
+        .any(|c| matches!(c, ReadingChange::value(&v) if v == 0.4)));
+    assert!(changes.iter().any(|c| matches!(c, ReadingChange::gain(_))));
+    assert!(changes
+        .iter()
+        .any(|c| matches!(c, ReadingChange::unit(u) if *u == "V")));
+
+    assert!(diff_changes(&Sample::Level(1.0), &Sample::Level(1.2)).is_empty());
+    assert_eq!(
+        diff_changes(&Sample::Level(1.0), &Sample::Level(2.0)).len(),
+        2
+    );
+    assert_eq!(diff_changes(&Sample::Level(1.0), &Sample::Off).len(), 2);
+}
+
+/// No `PartialEq`: `self_` follows the field changes.
+#[derive(Diff, Clone, Debug)]
+struct Draft {
+    title: String,
+    #[differs(skip)]
+    cursor: usize,
+}
+
+#[derive(Diff, Clone, Debug)]
+#[differs(no_self)]
+struct Quiet {
+    title: String,
+    draft: Draft,
+}
+
+#[test]
+fn self_follows_field_changes() {
+    let old = Draft {
+        title: "a".into(),
+        cursor: 0,
+    };
+    /* skipped fields no longer count */
+    let moved = Draft {
+        cursor: 5,
+        ..old.clone()
+    };
+    assert!(diff_changes(&old, &moved).is_empty());
+
+    let new = Draft {
+        title: "b".into(),
+        ..moved
+    };
+    let changes = diff_changes(&old, &new);
+    assert!(matches!(
+        changes[..],
+        [DraftChange::self_(_), DraftChange::title(_)]
+    ));
+
+    let old = Quiet {
+        title: "a".into(),
+        draft: old,
+    };
+    let new = Quiet {
+        title: "b".into(),
+        draft: new,
+    };
+    let changes = diff_changes(&old, &new);
+    assert!(matches!(
+        changes[..],
+        [
+            QuietChange::title(_),
+            QuietChange::draft(DraftChange::self_(_)),
+            QuietChange::draft(DraftChange::title(_)),
+        ]
+    ));
+}
+
+#[derive(Diff, Clone, Debug, PartialEq, Eq, Hash)]
+#[differs(owned)]
+struct Tag {
+    name: String,
+    weight: u32,
+}
+
+#[derive(Diff, Clone, Debug, PartialEq)]
+#[differs(owned)]
+enum Status {
+    Open,
+    Closed { reason: String },
+}
+
+#[derive(Diff, Clone, Debug, PartialEq)]
+#[differs(owned)]
+struct Ticket {
+    title: String,
+    tags: Vec<Tag>,
+    labels: HashMap<String, u32>,
+    assignee: Option<String>,
+    status: Status,
+    #[differs(old_new)]
+    priority: u8,
+}
+
+#[derive(Diff, Clone, Debug, PartialEq)]
+#[differs(owned)]
+struct Labelled<T> {
+    label: String,
+    value: T,
+}
+
+fn detached<T: Clone + Send + 'static>(value: T) -> T {
+    value.clone()
+}
+
+#[test]
+fn owned_changes_outlive_inputs() {
+    let owned: Vec<TicketChangeOwned> = {
+        let old = Ticket {
+            title: "crash".into(),
+            tags: vec![Tag {
+                name: "bug".into(),
+                weight: 1,
+            }],
+            labels: HashMap::from([("area".into(), 1)]),
+            assignee: None,
+            status: Status::Open,
+            priority: 1,
+        };
+        let mut new = old.clone();
+        new.title = "crash on save".into();
+        new.tags[0].weight = 3;
+        new.labels.insert("area".into(), 2);
+        new.assignee = Some("kim".into());
+        new.status = Status::Closed {
+            reason: "fixed".into(),
+        };
+        new.priority = 2;
+        diff_changes(&old, &new)
+            .into_iter()
+            .map(IntoOwned::into_owned)
+            .collect()
+    };
+    let owned = std::thread::spawn(move || detached(owned)).join().unwrap();
+
+    assert!(matches!(&owned[0], TicketChangeOwned::self_(s) if s.title == "crash on save"));
+    assert!(owned
+        .iter()
+        .any(|c| matches!(c, TicketChangeOwned::title(t) if t == "crash on save")));
+    assert!(owned.iter().any(|c| matches!(
+        c,
+        TicketChangeOwned::tags(ChangedOwned::ModifiedAt(0, tag, subs))
+            if tag.weight == 3 && matches!(subs[..], [TagChangeOwned::self_(_), TagChangeOwned::weight(3)])
+    )));
+    assert!(owned.iter().any(|c| matches!(
+        c,
+        TicketChangeOwned::labels(MapChangedOwned::ChangedEntry(k, 1, 2)) if k == "area"
+    )));
+    assert!(owned.iter().any(|c| matches!(
+        c,
+        TicketChangeOwned::assignee(OptionChangedOwned::Set(a)) if a == "kim"
+    )));
+    assert!(owned.iter().any(|c| matches!(
+        c,
+        TicketChangeOwned::status(StatusChangeOwned::VariantChanged {
+            new: StatusSnapshotOwned::Closed { reason },
+            ..
+        }) if reason == "fixed"
+    )));
+    assert!(owned
+        .iter()
+        .any(|c| matches!(c, TicketChangeOwned::priority(1, 2))));
+
+    let owned: Vec<LabelledChangeOwned<Tag>> = {
+        let old = Labelled {
+            label: "x".into(),
+            value: Tag {
+                name: "a".into(),
+                weight: 1,
+            },
+        };
+        let mut new = old.clone();
+        new.value.name = "b".into();
+        diff_changes(&old, &new)
+            .into_iter()
+            .map(IntoOwned::into_owned)
+            .collect()
+    };
+    assert!(matches!(
+        detached(owned)[..],
+        [
+            LabelledChangeOwned::self_(_),
+            LabelledChangeOwned::value(TagChangeOwned::self_(_)),
+            LabelledChangeOwned::value(TagChangeOwned::name(_)),
+        ]
+    ));
+}
+
+#[derive(Diff, Clone, Debug, PartialEq, Eq, Hash)]
+#[differs(patch)]
+struct Item {
+    sku: u32,
+    name: String,
+}
+
+#[derive(Diff, Clone, Debug, PartialEq)]
+#[differs(patch)]
+enum Phase {
+    Draft,
+    Live { since: u32 },
+    Archived(String),
+}
+
+#[derive(Diff, Clone, Debug, PartialEq)]
+#[differs(patch)]
+struct Board {
+    name: String,
+    #[differs(old_new)]
+    version: u32,
+    owner: Item,
+    items: Vec<Item>,
+    #[differs(key = sku)]
+    keyed: Vec<Item>,
+    #[differs(algorithm = "myers", ranges)]
+    lines: Vec<String>,
+    tags: BTreeSet<String>,
+    scores: HashMap<String, u32>,
+    shelves: BTreeMap<u32, Item>,
+    note: Option<Item>,
+    phase: Phase,
+    pair: (u8, String),
+    boxed: Box<Item>,
+}
+
+fn item(sku: u32, name: &str) -> Item {
+    Item {
+        sku,
+        name: name.into(),
+    }
+}
+
+fn board() -> Board {
+    Board {
+        name: "inbox".into(),
+        version: 1,
+        owner: item(0, "kim"),
+        items: vec![item(1, "a"), item(2, "b"), item(3, "c")],
+        keyed: vec![item(1, "a"), item(2, "b"), item(3, "c")],
+        lines: vec!["x".into(), "y".into(), "z".into()],
+        tags: BTreeSet::from(["bug".into(), "ui".into()]),
+        scores: HashMap::from([("kim".into(), 1), ("lee".into(), 2)]),
+        shelves: BTreeMap::from([(1, item(1, "a")), (2, item(2, "b"))]),
+        note: Some(item(9, "n")),
+        phase: Phase::Live { since: 1 },
+        pair: (1, "p".into()),
+        boxed: Box::new(item(5, "boxed")),
+    }
+}
+
+fn patch_of(old: &Board, new: &Board) -> Vec<BoardChangeOwned> {
+    diff_changes(old, new)
+        .into_iter()
+        .map(IntoOwned::into_owned)
+        .collect()
+}
+
+#[test]
+fn patches_reproduce_new() {
+    let old = board();
+    let mut new = board();
+    new.name = "done".into();
+    new.version = 2;
+    new.owner.name = "lee".into();
+    new.items = vec![item(3, "c"), item(4, "d"), item(1, "a")];
+    new.keyed = vec![item(3, "c!"), item(1, "a"), item(5, "e")];
+    new.lines = vec!["w".into(), "x".into(), "q".into(), "r".into()];
+    new.tags = BTreeSet::from(["bug".into(), "ux".into()]);
+    new.scores = HashMap::from([("kim".into(), 5), ("max".into(), 3)]);
+    new.shelves = BTreeMap::from([(2, item(2, "b!")), (3, item(3, "c"))]);
+    new.note.as_mut().unwrap().name = "m".into();
+    new.phase = Phase::Live { since: 2 };
+    new.pair.1 = "q".into();
+    new.boxed.sku = 6;
+
+    let mut patched = old.clone();
+    patched.apply_changes(&patch_of(&old, &new)).unwrap();
+    assert_eq!(patched, new);
+
+    /* variant switches and `None` */
+    let mut newer = new.clone();
+    newer.phase = Phase::Archived("old".into());
+    newer.note = None;
+    newer.items.clear();
+    let mut patched = new.clone();
+    differs::apply_changes(&mut patched, &patch_of(&new, &newer)).unwrap();
+    assert_eq!(patched, newer);
+
+    /* std containers patch on their own */
+    let (old, new) = (vec![1, 2, 3, 4], vec![4, 2, 5]);
+    let patch: Vec<_> = diff_changes(&old, &new)
+        .into_iter()
+        .map(IntoOwned::into_owned)
+        .collect();
+    let mut patched = old.clone();
+    differs::apply_changes(&mut patched, &patch).unwrap();
+    assert_eq!(patched, new);
+}
+
+#[test]
+fn patch_rejects_other_base() {
+    let old = board();
+    let mut new = board();
+    new.version = 2;
+    new.items.remove(1);
+    let patch = patch_of(&old, &new);
+
+    let mut other = board();
+    other.version = 7;
+    let err = other.apply_changes(&patch).unwrap_err();
+    assert_eq!(
+        (err.kind(), err.path()),
+        (PatchErrorKind::Mismatch, "version".into())
+    );
+
+    let mut other = board();
+    other.items[1].name = "elsewhere".into();
+    let err = other.apply_changes(&patch).unwrap_err();
+    assert_eq!(
+        (err.kind(), err.path()),
+        (PatchErrorKind::Mismatch, "items.1".into())
+    );
+    assert_eq!(
+        err.to_string(),
+        "patch does not apply at `items.1`: value differs from the patch's base"
+    );
+
+    let mut new = board();
+    new.phase = Phase::Live { since: 5 };
+    let mut other = board();
+    other.phase = Phase::Draft;
+    let err = other.apply_changes(&patch_of(&board(), &new)).unwrap_err();
+    assert_eq!(
+        (err.kind(), err.path()),
+        (PatchErrorKind::Variant, "phase.Live".into())
+    );
+}
+
+#[test]
+fn changes_report_field_paths() {
+    let address = Address {
+        street: "Main".into(),
+        city: "Oslo".into(),
+        zip: "0150".into(),
+    };
+    let old = Person {
+        id: 1,
+        name: "Ada".into(),
+        address: address.clone(),
+        tags: vec!["a".into()],
+        roles: HashSet::new(),
+        metadata: HashMap::from([("theme".into(), "dark".into())]),
+    };
+    let mut new = old.clone();
+    new.address.city = "Bergen".into();
+    new.tags.push("b".into());
+    new.metadata.insert("theme".into(), "light".into());
+
+    let paths: Vec<String> = diff_changes(&old, &new)
+        .iter()
+        .map(|c| c.path().as_str().to_owned())
+        .collect();
+    assert_eq!(
+        paths,
+        [
+            "",
+            "address",
+            "address.city",
+            "tags[1]",
+            r#"metadata["theme"]"#
+        ]
+    );
+
+    /* an element with one changed field reports that field */
+    let member = |id, name: &str| Member {
+        id,
+        name: name.into(),
+    };
+    let old = Team {
+        members: vec![member(1, "a"), member(2, "b")],
+    };
+    let mut new = old.clone();
+    new.members[1].name = "c".into();
+    let changes = diff_changes(&old, &new);
+    assert_eq!(changes[1].path().as_str(), "members[1].name");
+    new.members[1].id = 3;
+    let changes = diff_changes(&old, &new);
+    assert_eq!(changes[1].path().as_str(), "members[1]");
+
+    /* enum variants as the `Fields` derive names them */
+    let session = |peers| Session {
+        token: "t".into(),
+        peers,
+    };
+    let old = ConnectionState::Connected(session(vec![1]));
+    let new = ConnectionState::Connected(session(vec![2]));
+    let paths: Vec<String> = diff_changes(&old, &new)
+        .iter()
+        .map(|c| c.path().as_str().to_owned())
+        .collect();
+    assert!(
+        paths.contains(&"Connected.item0.peers[0]".to_owned()),
+        "{paths:?}"
+    );
+    let changes = diff_changes(&old, &ConnectionState::Idle);
+    assert_eq!(changes[1].path().as_str(), "");
+}
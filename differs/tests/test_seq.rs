@@ -0,0 +1,135 @@
+use differs::{
+    diff_changes, myers_lcs, ordered_changes, patience_lcs,
+    Changed::{AddedAt, Moved, RemovedAt},
+    HasChanges,
+};
+
+/// Length of the longest common subsequence, by dynamic programming.
+fn lcs_len<T: PartialEq>(a: &[T], b: &[T]) -> usize {
+    let mut dp = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for i in 0..a.len() {
+        for j in 0..b.len() {
+            dp[i + 1][j + 1] = if a[i] == b[j] {
+                dp[i][j] + 1
+            } else {
+                dp[i][j + 1].max(dp[i + 1][j])
+            };
+        }
+    }
+    dp[a.len()][b.len()]
+}
+
+fn assert_valid(a: &[char], b: &[char], pairs: &[(usize, usize)]) {
+    for w in pairs.windows(2) {
+        assert!(
+            w[0].0 < w[1].0 && w[0].1 < w[1].1,
+            "not ascending: {pairs:?}"
+        );
+    }
+    for &(i, j) in pairs {
+        assert_eq!(a[i], b[j]);
+    }
+}
+
+#[test]
+fn myers_finds_a_longest_common_subsequence() {
+    let cases = [
+        ("", ""),
+        ("abc", ""),
+        ("", "abc"),
+        ("abcabba", "cbabac"),
+        ("kitten", "sitting"),
+        ("aaaa", "aaa"),
+        ("xyz", "xyz"),
+    ];
+    for (a, b) in cases {
+        let (a, b): (Vec<char>, Vec<char>) = (a.chars().collect(), b.chars().collect());
+        let pairs = myers_lcs(&a, &b);
+        assert_valid(&a, &b, &pairs);
+        assert_eq!(pairs.len(), lcs_len(&a, &b), "{a:?} -> {b:?}");
+    }
+}
+
+#[test]
+fn myers_matches_dynamic_programming() {
+    let mut seed = 0x2545_f491_u32;
+    let mut next = |n: u32| {
+        seed ^= seed << 13;
+        seed ^= seed >> 17;
+        seed ^= seed << 5;
+        seed % n
+    };
+    for _ in 0..300 {
+        let mut side = |len| -> Vec<char> {
+            (0..next(len))
+                .map(|_| char::from(b'a' + next(4) as u8))
+                .collect()
+        };
+        let (a, b) = (side(24), side(24));
+        let pairs = myers_lcs(&a, &b);
+        assert_valid(&a, &b, &pairs);
+        assert_eq!(pairs.len(), lcs_len(&a, &b), "{a:?} -> {b:?}");
+    }
+}
+
+#[test]
+fn myers_handles_large_inputs() {
+    /* nothing in common: D = N + M */
+    let old: Vec<u32> = (0..4000).collect();
+    let new: Vec<u32> = (4000..8000).collect();
+    assert!(myers_lcs(&old, &new).is_empty());
+
+    /* a bulk insert in front, with the last element edited */
+    let old: Vec<u32> = (0..5000).collect();
+    let mut new: Vec<u32> = (10_000..15_000).chain(0..5000).collect();
+    *new.last_mut().unwrap() = 7;
+    let pairs = myers_lcs(&old, &new);
+    assert_eq!(pairs.len(), 4999);
+    assert_eq!(pairs[0], (0, 5000));
+}
+
+#[test]
+fn patience_anchors_on_unique_lines() {
+    let a: Vec<char> = "abcXdefYg".chars().collect();
+    let b: Vec<char> = "YabcXdefg".chars().collect();
+    let pairs = patience_lcs(&a, &b, None);
+    assert_valid(&a, &b, &pairs);
+    /* `Y` jumping to the front must not break up the `abcXdef` run */
+    assert_eq!(pairs.len(), 8);
+
+    let a: Vec<char> = "aaaa".chars().collect();
+    let b: Vec<char> = "aa".chars().collect();
+    assert_eq!(patience_lcs(&a, &b, None).len(), 2);
+    assert_eq!(patience_lcs(&a, &b, char::EQ_HASH).len(), 2);
+}
+
+#[test]
+fn ordered_changes_only_reports_genuine_moves() {
+    let old: Vec<u32> = (0..1000).collect();
+    let new: Vec<u32> = old[1..].to_vec();
+    let changes = ordered_changes::<_, ()>(&old, &new, &myers_lcs(&old, &new), None, None);
+    assert_eq!(changes, [RemovedAt(0, &0, 0)]);
+
+    let old = ['a', 'b', 'c', 'd'];
+    let new = ['d', 'a', 'b', 'c', 'e'];
+    let changes = ordered_changes::<_, ()>(&old, &new, &myers_lcs(&old, &new), None, None);
+    assert_eq!(changes, [Moved(&'d', 3, 0), AddedAt(4, &'e', 0)]);
+}
+
+#[test]
+fn bulk_inserts_stay_linear() {
+    /* 20k fresh values in the middle of 20k, then a duplicate of a kept one */
+    let old: Vec<u32> = (0..20_000).collect();
+    let new: Vec<u32> = (0..10_000)
+        .chain(100_000..120_000)
+        .chain([5])
+        .chain(10_000..20_000)
+        .collect();
+    let changes = diff_changes(&old, &new);
+    assert_eq!(changes.len(), 20_001);
+    assert!(changes[..20_000]
+        .iter()
+        .enumerate()
+        .all(|(k, c)| matches!(c, AddedAt(idx, _, 0) if *idx == 10_000 + k)));
+    assert!(matches!(changes[20_000], AddedAt(30_000, &5, 1)));
+}
@@ -0,0 +1,522 @@
+#![cfg(feature = "serde")]
+
+use differs::{
+    apply_merge_patch, diff_changes, json_patch, merge_patch, serialized_changes, ChangeEventBus,
+    Diff, FieldName, Fields, HasFields, IntoOwned, ValueChangeOwned,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
+
+#[derive(Diff, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[differs(serde, owned)]
+struct Tag {
+    name: String,
+    weight: u32,
+}
+
+#[derive(Diff, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[differs(serde, owned)]
+enum Status {
+    Open,
+    Closed { reason: String },
+}
+
+#[derive(Diff, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[differs(serde, owned)]
+struct Ticket {
+    title: String,
+    tags: Vec<Tag>,
+    labels: BTreeMap<String, u32>,
+    assignee: Option<String>,
+    status: Status,
+    size: (u32, u32),
+    #[differs(old_new)]
+    priority: u8,
+}
+
+/// Borrowed changes only serialize.
+#[derive(Diff, Clone, Debug, PartialEq, Serialize)]
+#[differs(serde)]
+struct Wrapper<T> {
+    value: T,
+}
+
+fn ticket() -> Ticket {
+    Ticket {
+        title: "crash".into(),
+        tags: vec![Tag {
+            name: "bug".into(),
+            weight: 1,
+        }],
+        labels: BTreeMap::from([("area".into(), 1)]),
+        assignee: None,
+        status: Status::Open,
+        size: (1, 1),
+        priority: 1,
+    }
+}
+
+#[test]
+fn changes_serialize_with_field_paths() {
+    let old = ticket();
+    let mut new = old.clone();
+    new.tags[0].weight = 3;
+    new.tags.push(Tag {
+        name: "ui".into(),
+        weight: 1,
+    });
+    new.labels.insert("area".into(), 2);
+    new.labels.insert("os".into(), 7);
+    new.assignee = Some("kim".into());
+    new.status = Status::Closed {
+        reason: "fixed".into(),
+    };
+    new.size.1 = 2;
+    new.priority = 2;
+
+    let changes = diff_changes(&old, &new);
+    let wire = serde_json::to_value(&changes[1..]).unwrap();
+    assert_eq!(
+        wire,
+        json!([
+            {"tags": {
+                "op": "modified_at",
+                "index": 0,
+                "value": {"name": "bug", "weight": 3},
+                "changes": [{"self_": {"name": "bug", "weight": 3}}, {"weight": 3}],
+            }},
+            {"tags": {"op": "added_at", "index": 1, "value": {"name": "ui", "weight": 1}, "nth": 0}},
+            {"labels": {"op": "changed_entry", "key": "area", "old": 1, "new": 2}},
+            {"labels": {"op": "added_entry", "key": "os", "value": 7}},
+            {"assignee": {"op": "set", "value": "kim"}},
+            {"status": {"self_": {"Closed": {"reason": "fixed"}}}},
+            {"status": {"VariantChanged": {"old": "Open", "new": {"Closed": {"reason": "fixed"}}}}},
+            {"size": {"item1": 2}},
+            {"priority": [1, 2]},
+        ])
+    );
+    assert_eq!(
+        serde_json::to_value(&changes[0]).unwrap()["self_"]["title"],
+        "crash"
+    );
+
+    let old = Wrapper { value: 1 };
+    let new = Wrapper { value: 2 };
+    assert_eq!(
+        serde_json::to_value(diff_changes(&old, &new)).unwrap(),
+        json!([{"self_": {"value": 2}}, {"value": 2}])
+    );
+}
+
+#[test]
+fn owned_changes_round_trip() {
+    let old = ticket();
+    let mut new = old.clone();
+    new.tags.remove(0);
+    new.labels.clear();
+    new.title = "crash on save".into();
+    new.priority = 3;
+
+    let owned: Vec<TicketChangeOwned> = diff_changes(&old, &new)
+        .into_iter()
+        .map(IntoOwned::into_owned)
+        .collect();
+    let text = serde_json::to_string(&owned).unwrap();
+    let back: Vec<TicketChangeOwned> = serde_json::from_str(&text).unwrap();
+    assert_eq!(serde_json::to_string(&back).unwrap(), text);
+    assert!(matches!(&back[1], TicketChangeOwned::title(t) if t == "crash on save"));
+    assert!(back
+        .iter()
+        .any(|c| matches!(c, TicketChangeOwned::priority(1, 3))));
+    assert!(back.iter().any(|c| matches!(
+        c,
+        TicketChangeOwned::labels(differs::MapChangedOwned::RemovedEntry(k, 1)) if k == "area"
+    )));
+}
+
+#[derive(Diff, Clone, Debug, PartialEq, Serialize)]
+#[differs(serde)]
+enum Stage {
+    Idle,
+    Running { pid: u32, args: Vec<String> },
+    Done(u32),
+    Failed(u32, String),
+}
+
+#[derive(Diff, Clone, Debug, PartialEq, Serialize)]
+#[differs(serde)]
+struct Point(i32, i32);
+
+#[derive(Diff, Clone, Debug, PartialEq, Serialize)]
+#[differs(serde)]
+struct Queue {
+    jobs: Vec<u32>,
+    #[differs(algorithm = "myers", ranges)]
+    lines: Vec<String>,
+    #[differs(key = name)]
+    tags: Vec<Tag>,
+    stage: Stage,
+    point: Point,
+}
+
+/// Applies `add` / `remove` / `replace` / `move` operations, strictly.
+fn apply_json_patch(doc: &mut Value, patch: &Value) {
+    fn split(path: &str) -> (&str, String) {
+        let (parent, key) = path.rsplit_once('/').unwrap();
+        (parent, key.replace("~1", "/").replace("~0", "~"))
+    }
+    fn take(doc: &mut Value, path: &str) -> Value {
+        let (parent, key) = split(path);
+        match doc.pointer_mut(parent).unwrap() {
+            Value::Array(a) => a.remove(key.parse().unwrap()),
+            Value::Object(o) => o.remove(&key).unwrap(),
+            other => panic!("no container at {parent}: {other}"),
+        }
+    }
+    fn insert(doc: &mut Value, path: &str, value: Value) {
+        if path.is_empty() {
+            *doc = value;
+            return;
+        }
+        let (parent, key) = split(path);
+        match doc.pointer_mut(parent).unwrap() {
+            Value::Array(a) if key == "-" => a.push(value),
+            Value::Array(a) => {
+                let idx: usize = key.parse().unwrap();
+                assert!(idx <= a.len(), "{path} past the end");
+                a.insert(idx, value);
+            }
+            Value::Object(o) => drop(o.insert(key, value)),
+            other => panic!("no container at {parent}: {other}"),
+        }
+    }
+    for op in patch.as_array().unwrap() {
+        let path = op["path"].as_str().unwrap();
+        match op["op"].as_str().unwrap() {
+            "add" => insert(doc, path, op["value"].clone()),
+            "remove" => drop(take(doc, path)),
+            "replace" => *doc.pointer_mut(path).unwrap() = op["value"].clone(),
+            "move" => {
+                let value = take(doc, op["from"].as_str().unwrap());
+                insert(doc, path, value);
+            }
+            other => panic!("unexpected op {other}"),
+        }
+    }
+}
+
+#[test]
+fn json_patch_uses_pointer_paths() {
+    let old = ticket();
+    let mut new = old.clone();
+    new.tags[0].weight = 3;
+    new.tags.push(Tag {
+        name: "ui".into(),
+        weight: 1,
+    });
+    new.labels.remove("area");
+    new.labels.insert("a/b".into(), 7);
+    new.assignee = Some("kim".into());
+    new.status = Status::Closed {
+        reason: "fixed".into(),
+    };
+    new.size.1 = 2;
+    new.priority = 2;
+
+    let patch = json_patch(&diff_changes(&old, &new)).unwrap();
+    assert_eq!(
+        patch,
+        json!([
+            {"op": "add", "path": "/tags/1", "value": {"name": "ui", "weight": 1}},
+            {"op": "replace", "path": "/tags/0/weight", "value": 3},
+            {"op": "add", "path": "/labels/a~1b", "value": 7},
+            {"op": "remove", "path": "/labels/area"},
+            {"op": "replace", "path": "/assignee", "value": "kim"},
+            {"op": "replace", "path": "/status", "value": {"Closed": {"reason": "fixed"}}},
+            {"op": "replace", "path": "/size/1", "value": 2},
+            {"op": "replace", "path": "/priority", "value": 2},
+        ])
+    );
+    let mut doc = serde_json::to_value(&old).unwrap();
+    apply_json_patch(&mut doc, &patch);
+    assert_eq!(doc, serde_json::to_value(&new).unwrap());
+}
+
+#[test]
+fn json_patch_turns_old_into_new() {
+    /* small xorshift: vectors with duplicates, shuffles and growth */
+    let mut seed = 0x2545_f491_u32;
+    let mut next = |n: u32| {
+        seed ^= seed << 13;
+        seed ^= seed >> 17;
+        seed ^= seed << 5;
+        seed % n
+    };
+    let list = |next: &mut dyn FnMut(u32) -> u32| -> Vec<u32> {
+        let len = next(7);
+        (0..len).map(|_| next(5)).collect()
+    };
+    let stages = [
+        Stage::Idle,
+        Stage::Running {
+            pid: 1,
+            args: vec!["-v".into()],
+        },
+        Stage::Running {
+            pid: 2,
+            args: vec!["-q".into(), "-v".into()],
+        },
+        Stage::Done(0),
+        Stage::Failed(1, "oom".into()),
+        Stage::Failed(2, "oom".into()),
+    ];
+    let queue = |next: &mut dyn FnMut(u32) -> u32| Queue {
+        jobs: list(next),
+        lines: list(next).iter().map(u32::to_string).collect(),
+        tags: list(next)
+            .into_iter()
+            .fold(Vec::<Tag>::new(), |mut tags, n| {
+                if !tags.iter().any(|t| t.name == n.to_string()) {
+                    tags.push(Tag {
+                        name: n.to_string(),
+                        weight: next(3),
+                    });
+                }
+                tags
+            }),
+        stage: stages[next(stages.len() as u32) as usize].clone(),
+        point: Point(next(3) as i32, next(3) as i32),
+    };
+
+    for _ in 0..500 {
+        let old = queue(&mut next);
+        let new = queue(&mut next);
+        let patch = json_patch(&diff_changes(&old, &new)).unwrap();
+        let mut doc = serde_json::to_value(&old).unwrap();
+        apply_json_patch(&mut doc, &patch);
+        assert_eq!(
+            doc,
+            serde_json::to_value(&new).unwrap(),
+            "{old:?} -> {new:?}: {patch}"
+        );
+    }
+}
+
+#[test]
+fn merge_patches_round_trip() {
+    let old = ticket();
+    let mut new = old.clone();
+    new.tags[0].weight = 3;
+    new.labels.remove("area");
+    new.labels.insert("os".into(), 7);
+    new.status = Status::Closed {
+        reason: "fixed".into(),
+    };
+    new.priority = 2;
+
+    let patch = merge_patch(&old, &new).unwrap();
+    assert_eq!(
+        patch,
+        json!({
+            "tags": [{"name": "bug", "weight": 3}],
+            "labels": {"area": null, "os": 7},
+            "status": {"Closed": {"reason": "fixed"}},
+            "priority": 2,
+        })
+    );
+    assert_eq!(merge_patch(&new, &new).unwrap(), json!({}));
+
+    let mut patched = old.clone();
+    let changes: Vec<TicketChangeOwned> = apply_merge_patch(&mut patched, &patch).unwrap();
+    assert_eq!(patched, new);
+    assert!(changes
+        .iter()
+        .any(|c| matches!(c, TicketChangeOwned::priority(1, 2))));
+}
+
+#[test]
+fn merge_patch_sets_and_unsets_members() {
+    let mut ticket = ticket();
+    let changes: Vec<TicketChangeOwned> = apply_merge_patch(
+        &mut ticket,
+        &json!({"assignee": "kim", "labels": {"os": 7}, "title": "crash on save"}),
+    )
+    .unwrap();
+    assert_eq!(ticket.assignee.as_deref(), Some("kim"));
+    assert_eq!(ticket.labels.len(), 2);
+    assert!(matches!(&changes[1], TicketChangeOwned::title(t) if t == "crash on save"));
+
+    let changes: Vec<TicketChangeOwned> =
+        apply_merge_patch(&mut ticket, &json!({"assignee": null})).unwrap();
+    assert_eq!(ticket.assignee, None);
+    assert!(matches!(
+        &changes[1],
+        TicketChangeOwned::assignee(differs::OptionChangedOwned::Unset(k)) if k == "kim"
+    ));
+
+    /* the patched form must still deserialize; the target is untouched */
+    let before = ticket.clone();
+    assert!(
+        apply_merge_patch::<_, TicketChangeOwned>(&mut ticket, &json!({"priority": "high"}))
+            .is_err()
+    );
+    assert_eq!(ticket, before);
+}
+
+#[test]
+fn serialized_values_diff_schemalessly() {
+    let old = ticket();
+    let mut new = old.clone();
+    new.tags[0].name = "crash".into();
+    new.labels.insert("os".into(), 7);
+
+    let changes = serialized_changes(&old, &new).unwrap();
+    let paths: Vec<&str> = changes.iter().map(|(p, _)| p.as_str()).collect();
+    assert_eq!(paths, ["labels.os", "tags[0].name"]);
+    assert!(matches!(
+        &changes[1].1,
+        ValueChangeOwned::Object(differs::MapChangedOwned::ChangedEntry(k, _, v))
+            if k == "name" && v == "crash"
+    ));
+
+    let old = json!({"jobs": [1, 2, 3], "env": {"a": 1}, "on": true});
+    let new = json!({"jobs": [3, 1, {"id": 4}], "env": {"b": [1]}, "on": null});
+    let patch = json_patch(&diff_changes(&old, &new)).unwrap();
+    let mut doc = old.clone();
+    apply_json_patch(&mut doc, &patch);
+    assert_eq!(doc, new);
+}
+
+#[test]
+fn diffs_publish_to_changed_paths() {
+    let old = ticket();
+    let mut new = old.clone();
+    new.tags[0].weight = 5;
+    new.labels.insert("env".into(), 7);
+    new.labels.remove("area");
+    new.size.1 = 30;
+    new.status = Status::Closed {
+        reason: "done".into(),
+    };
+
+    let mut bus = ChangeEventBus::new();
+    let mut sub = |path: &'static str| bus.subscribe(FieldName::static_lit(path));
+    let weight = sub("tags[0].weight");
+    let env = sub(r#"labels["env"]"#);
+    let area = sub(r#"labels["area"]"#);
+    let size = sub("size");
+    let status = sub("status");
+    let title = sub("title");
+    bus.publish_diff(&old, &new).unwrap();
+
+    assert_eq!(weight.try_recv().unwrap(), "5");
+    assert_eq!(env.try_recv().unwrap(), "7");
+    assert_eq!(area.try_recv().unwrap(), "null");
+    assert_eq!(size.try_recv().unwrap(), "[1,30]");
+    assert_eq!(
+        status.try_recv().unwrap(),
+        r#"{"Closed":{"reason":"done"}}"#
+    );
+    /* one message per path, none for untouched fields */
+    assert!(status.try_recv().is_err());
+    assert!(title.try_recv().is_err());
+}
+
+#[derive(Diff, Fields, Clone, Debug, PartialEq, Serialize)]
+#[differs(serde)]
+struct Profile {
+    tags: Vec<String>,
+    prefs: HashMap<String, String>,
+}
+
+#[test]
+fn containers_publish_where_fields_reach_them() {
+    let old = Profile {
+        tags: vec!["a".into()],
+        prefs: HashMap::from([("theme".into(), "dark".into())]),
+    };
+    let mut new = old.clone();
+    new.tags.push("b".into());
+    new.prefs.insert("theme".into(), "light".into());
+
+    let mut bus = ChangeEventBus::new();
+    let tags = bus.subscribe(Profile::fields().tags());
+    let prefs = bus.subscribe(Profile::fields().prefs());
+    let second = bus.subscribe(FieldName::static_lit("tags[1]"));
+    bus.publish_diff(&old, &new).unwrap();
+
+    assert_eq!(tags.try_recv().unwrap(), r#"["a","b"]"#);
+    assert_eq!(prefs.try_recv().unwrap(), r#"{"theme":"light"}"#);
+    assert_eq!(second.try_recv().unwrap(), r#""b""#);
+    assert!(tags.try_recv().is_err());
+}
+
+#[test]
+fn shifted_elements_publish_what_now_sits_at_their_path() {
+    let mut old = ticket();
+    old.tags.push(Tag {
+        name: "ui".into(),
+        weight: 2,
+    });
+    let mut new = old.clone();
+    new.tags.remove(0);
+
+    let mut bus = ChangeEventBus::new();
+    let first = bus.subscribe(FieldName::static_lit("tags[0]"));
+    let second = bus.subscribe(FieldName::static_lit("tags[1]"));
+    bus.publish_diff(&old, &new).unwrap();
+
+    /* `bug` was removed at 0 and `ui` moved from 1 into its place */
+    assert_eq!(first.try_recv().unwrap(), r#"{"name":"ui","weight":2}"#);
+    assert_eq!(second.try_recv().unwrap(), "null");
+    assert!(first.try_recv().is_err());
+}
+
+#[derive(Diff, Fields, Clone, Debug, PartialEq, Serialize)]
+#[differs(serde, no_self)]
+struct Backlog {
+    jobs: Vec<u32>,
+}
+
+#[derive(Diff, Fields, Clone, Debug, PartialEq, Serialize)]
+#[differs(serde, no_self)]
+struct Worker {
+    queue: Backlog,
+}
+
+#[test]
+fn moves_publish_their_source_and_every_ancestor() {
+    let old = Worker {
+        queue: Backlog {
+            jobs: vec![1, 2, 3],
+        },
+    };
+    let new = Worker {
+        queue: Backlog {
+            jobs: vec![3, 1, 2],
+        },
+    };
+
+    let mut bus = ChangeEventBus::new();
+    let root = bus.subscribe(FieldName::static_lit(""));
+    let queue = bus.subscribe(FieldName::static_lit("queue"));
+    let jobs = bus.subscribe(Worker::fields().queue().jobs());
+    let mut sub = |path: &'static str| bus.subscribe(FieldName::static_lit(path));
+    let slots = [
+        sub("queue.jobs[0]"),
+        sub("queue.jobs[1]"),
+        sub("queue.jobs[2]"),
+    ];
+    bus.publish_diff(&old, &new).unwrap();
+
+    /* no `self_` variant: ancestors hear about the change all the same */
+    assert_eq!(root.try_recv().unwrap(), r#"{"queue":{"jobs":[3,1,2]}}"#);
+    assert_eq!(queue.try_recv().unwrap(), r#"{"jobs":[3,1,2]}"#);
+    assert_eq!(jobs.try_recv().unwrap(), "[3,1,2]");
+    for (slot, value) in slots.iter().zip(["3", "1", "2"]) {
+        assert_eq!(slot.try_recv().unwrap(), value);
+        assert!(slot.try_recv().is_err());
+    }
+    assert!(root.try_recv().is_err());
+}
@@ -0,0 +1,62 @@
+use differs::{diff_changes, value_changes, Changed, FieldName, MapChanged, ValueChange};
+use serde_json::json;
+
+#[test]
+fn values_diff_like_derived_types() {
+    let old = json!({"name": "lint", "level": 1, "rules": ["a", "b"], "env": {"CI": true}});
+    let new = json!({"name": "lint", "level": 2, "rules": ["b", "c"], "env": {}});
+
+    let changes = diff_changes(&old, &new);
+    assert!(matches!(
+        changes[0],
+        ValueChange::Object(MapChanged::ModifiedEntry(k, ref subs))
+            if k == "env" && matches!(subs[..], [ValueChange::Object(MapChanged::RemovedEntry(..))])
+    ));
+    assert!(changes.iter().any(|c| matches!(
+        c,
+        ValueChange::Object(MapChanged::ChangedEntry(k, o, n)) if *k == "level" && **o == 1 && **n == 2
+    )));
+    assert!(diff_changes(&new, &new).is_empty());
+    assert_eq!(
+        diff_changes(&json!(1), &json!("1")),
+        [ValueChange::Replaced(&json!("1"))]
+    );
+}
+
+#[test]
+fn value_changes_carry_paths() {
+    let old = json!({
+        "plugins": [{"name": "fmt", "args": ["-q"]}, {"name": "lint"}],
+        "env": {"PATH": "/bin", "no dots": 1},
+    });
+    let new = json!({
+        "plugins": [{"name": "fmt", "args": ["-v"]}, {"name": "lint"}, {"name": "doc"}],
+        "env": {"PATH": "/usr/bin"},
+        "debug": true,
+    });
+
+    let changes = value_changes(&old, &new);
+    let paths: Vec<&str> = changes.iter().map(|(p, _)| p.as_str()).collect();
+    assert_eq!(
+        paths,
+        [
+            "env.PATH",
+            r#"env["no dots"]"#,
+            "plugins[0].args[0]",
+            "plugins[2]",
+            "debug",
+        ]
+    );
+    assert!(matches!(
+        changes[2].1,
+        ValueChange::Array(Changed::ModifiedAt(0, v, ref subs)) if v == "-v" && subs.is_empty()
+    ));
+    assert_eq!(
+        FieldName::static_lit("a")
+            .child("b")
+            .index(3)
+            .key("k")
+            .as_str(),
+        r#"a.b[3]["k"]"#
+    );
+}